@@ -1,8 +1,17 @@
 pub mod quad_tree_bounds_ts;
 pub mod quad_tree_leaf_ts;
 pub mod quad_tree_branch_ts;
+pub mod quad_tree_ts;
+pub mod quad_tree_snapshot_ts;
+pub mod quad_tree_polygon_ts;
 
 
 pub mod quad_tree_bounds;
 pub mod quad_tree_leaf;
 pub mod quad_tree;
+
+pub mod quad_tree_config;
+pub mod quad_tree_error;
+pub mod quad_tree_node_info;
+pub mod quad_tree_orientation;
+pub mod quad_tree_scalar;
@@ -1,30 +1,61 @@
 use std::{
+    array,
     cell::RefCell,
     mem, ptr,
     rc::{Rc, Weak},
+    vec::IntoIter,
 };
 
 use crate::{
     quad_tree_bounds::QuadTreeBounds,
+    quad_tree_config::QuadTreeConfig,
+    quad_tree_error::QuadTreeError,
     quad_tree_leaf::{QuadTreeLeaf},
+    quad_tree_node_info::QuadTreeNodeInfo,
+    quad_tree_orientation::QuadTreeOrientation,
+    quad_tree_scalar::{QuadTreeScalar, max},
 };
 
-pub struct QuadTree {
+// one slot per quadrant, empty until something sinks into it
+type QuadTreeBranches<T, S> = Vec<Option<Rc<RefCell<QuadTree<T, S>>>>>;
+
+pub struct QuadTree<T, S = i64>
+where
+    T: Clone,
+    S: QuadTreeScalar,
+{
     pub root: bool,
-    pub bounds: QuadTreeBounds,
-    pub items: Vec<QuadTreeLeaf>,
-    pub stuck: Vec<QuadTreeLeaf>,
-    pub branches: Vec<Option<Rc<RefCell<QuadTree>>>>,
-    pub parent: Option<Weak<RefCell<QuadTree>>>,
+    pub bounds: QuadTreeBounds<S>,
+    pub config: QuadTreeConfig<S>,
+    count: usize,
+    pub items: Vec<QuadTreeLeaf<T, S>>,
+    pub stuck: Vec<QuadTreeLeaf<T, S>>,
+    pub branches: QuadTreeBranches<T, S>,
+    pub parent: Option<Weak<RefCell<QuadTree<T, S>>>>,
 }
 
-impl QuadTree {
+impl<T, S> QuadTree<T, S>
+where
+    T: Clone,
+    S: QuadTreeScalar,
+{
     pub fn new(
         root: bool,
-        x: i64,
-        y: i64,
-        size: i64,
-        parent: Option<Weak<RefCell<QuadTree>>>,
+        x: S,
+        y: S,
+        size: S,
+        parent: Option<Weak<RefCell<QuadTree<T, S>>>>,
+    ) -> Self {
+        QuadTree::with_config(root, x, y, size, parent, QuadTreeConfig::default())
+    }
+
+    pub fn with_config(
+        root: bool,
+        x: S,
+        y: S,
+        size: S,
+        parent: Option<Weak<RefCell<QuadTree<T, S>>>>,
+        config: QuadTreeConfig<S>,
     ) -> Self {
         let mut branches = Vec::new();
         for _ in 0..4 {
@@ -33,14 +64,35 @@ impl QuadTree {
         Self {
             root,
             bounds: QuadTreeBounds::new(x, y, size, size),
-            items: Vec::with_capacity(2),
+            config,
+            count: 0,
+            items: Vec::new(),
             stuck: Vec::new(),
             branches,
             parent,
         }
     }
 
+    // a root with no size yet, the first insert centres it on that leaf
+    pub fn auto(config: QuadTreeConfig<S>) -> Self {
+        let zero = S::zero();
+        QuadTree::with_config(true, zero, zero, zero, None, config)
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn branch_lens(&self) -> [usize; 4] {
+        array::from_fn(|i| self.branches[i].as_ref().map_or(0, |branch| branch.borrow().count))
+    }
+
     pub fn clear(&mut self) {
+        self.count = 0;
         self.items.clear();
         self.stuck.clear();
         self.branches.clear();
@@ -49,93 +101,142 @@ impl QuadTree {
         }
     }
 
-    fn index(&self, bounds: &QuadTreeBounds) -> isize {
+    // the area this node's subtree can reach, wider than its bounds in loose mode
+    pub fn loose_bounds(&self) -> QuadTreeBounds<S> {
+        self.bounds.expand(self.config.margin(self.bounds.w))
+    }
+
+    fn orientation(&self) -> QuadTreeOrientation {
+        self.config.orientation.unwrap_or(QuadTreeOrientation::YDown)
+    }
+
+    fn quadrant(
+        bounds: &QuadTreeBounds<S>,
+        index: usize,
+        orientation: QuadTreeOrientation,
+    ) -> QuadTreeBounds<S> {
+        let size = bounds.w.half();
+        let mut x = bounds.x;
+        let mut y = bounds.y;
+
+        let (right, high_y) = orientation.halves(index);
+        if right {
+            x = x + size;
+        }
+        if high_y {
+            y = y + size;
+        }
+        QuadTreeBounds::new(x, y, size, size)
+    }
+
+    fn index(&self, bounds: &QuadTreeBounds<S>) -> isize {
         // 0 1
         // 3 2
-        let ymid = self.bounds.y + (self.bounds.h / 2);
-        let xmid = self.bounds.x + (self.bounds.w / 2);
-
-        if bounds.y + bounds.h <= ymid {
-            // top half
-            if bounds.x + bounds.w <= xmid {
-                // left half
-                return 0;
-            }
-            if bounds.x >= xmid {
-                // right half
-                return 1;
-            }
-        } else if bounds.y >= ymid {
-            // bottom half
-            if bounds.x + bounds.w <= xmid {
-                // left half
-                return 3;
-            }
-            if bounds.x >= xmid {
-                // right half
-                return 2;
-            }
+        let ymid = self.bounds.y + self.bounds.h.half();
+        let xmid = self.bounds.x + self.bounds.w.half();
+
+        let orientation = self.orientation();
+        if self.config.is_loose() {
+            // loose children are picked by the centre, the caller checks the leaf fits the loose bounds
+            let x = bounds.x + bounds.w.half();
+            let y = bounds.y + bounds.h.half();
+            return orientation.index(x >= xmid, y >= ymid) as isize;
         }
-        return -1;
+
+        // a leaf sitting on a midpoint goes to the high side, the same as contains_point
+        let right = if bounds.x >= xmid {
+            true
+        } else if bounds.right() <= xmid {
+            false
+        } else {
+            return -1;
+        };
+        let high_y = if bounds.y >= ymid {
+            true
+        } else if bounds.bottom() <= ymid {
+            false
+        } else {
+            return -1;
+        };
+        orientation.index(right, high_y) as isize
     }
 
-    pub fn remove(leaf: &QuadTreeLeaf) ->bool {
-        let mut removed = false;
-        let foo = leaf.parent.upgrade().unwrap();
+    pub fn remove(leaf: &QuadTreeLeaf<T, S>) -> Result<(), QuadTreeError> {
+        let foo = leaf.get_parent().ok_or(QuadTreeError::Detached)?;
+        let mut root = foo.clone();
+        let empty;
+        let auto_shrink;
         {
             let mut tree = foo.borrow_mut();
 
             
 
-            tree.items.retain(|l| {
-                let retain = l.identity != leaf.identity;
-                if !retain {
-                    removed = true;
-                }
-                retain
-            });
-
-            if !removed {
-                tree.stuck.retain(|l| {
-                    let retain = l.identity != leaf.identity;
-                    if !retain {
-                        removed = true;
-                    }
-                    retain
-                });
+            match tree.find_leaf(leaf.get_slot(), leaf.identity) {
+                Some((false, slot)) => QuadTree::take_leaf(&mut tree.items, slot),
+                Some((true, slot)) => QuadTree::take_leaf(&mut tree.stuck, slot),
+                None => return Err(QuadTreeError::NotFound),
+            };
+            leaf.set_parent(Weak::new());
+
+            tree.count -= 1;
+            let mut parent = tree.parent.clone();
+            while let Some(next_rc) = parent.and_then(|p| p.upgrade()) {
+                let mut next = next_rc.borrow_mut();
+                next.count -= 1;
+                parent = next.parent.clone();
+                drop(next);
+                root = next_rc;
             }
 
-            if tree.items.len() > 0 {
-                return removed;
-            }
-            if tree.stuck.len() > 0 {
-                return removed;
-            }
+            empty = tree.items.is_empty()
+                && tree.stuck.is_empty()
+                && tree.branches.iter().all(|b| b.is_none());
+            auto_shrink = tree.config.auto_shrink;
+        }
+        if empty {
+            QuadTree::_actr_quad_tree_remove_tree(foo, 0);
+        }
+        if auto_shrink {
+            QuadTree::shrink(root);
+        }
+        Ok(())
+    }
 
-            for branch in tree.branches.iter() {
-                if branch.is_some() {
-                    return removed;
-                }
-            }
+    fn push_leaf(list: &mut Vec<QuadTreeLeaf<T, S>>, leaf: QuadTreeLeaf<T, S>) {
+        leaf.set_slot(list.len());
+        list.push(leaf);
+    }
+
+    fn take_leaf(list: &mut Vec<QuadTreeLeaf<T, S>>, slot: usize) -> QuadTreeLeaf<T, S> {
+        let leaf = list.swap_remove(slot);
+        if let Some(moved) = list.get(slot) {
+            moved.set_slot(slot);
+        }
+        leaf
+    }
+
+    // true when the leaf is stuck, along with its slot in that list
+    fn find_leaf(&self, slot: usize, identity: i64) -> Option<(bool, usize)> {
+        if self.items.get(slot).is_some_and(|l| l.identity == identity) {
+            return Some((false, slot));
+        }
+        if self.stuck.get(slot).is_some_and(|l| l.identity == identity) {
+            return Some((true, slot));
         }
-        let foo = leaf.parent.upgrade().unwrap();
-        QuadTree::_actr_quad_tree_remove_tree(foo, 0);
-        removed
+        None
     }
 
     
-    fn _actr_quad_tree_remove_tree(child_rc: Rc<RefCell<QuadTree>>, level: usize) {
+    fn _actr_quad_tree_remove_tree(child_rc: Rc<RefCell<QuadTree<T, S>>>, level: usize) {
 
 
         let child_ref = child_rc.as_ref();
         let child_borrow = child_ref.borrow();
 
-        if child_borrow.parent.is_none() {
+        // nothing above to prune, or the parent has already been dropped
+        let Some(parent_rc) = child_borrow.parent.as_ref().and_then(|p| p.upgrade()) else {
             return;
-        }
-
-        let parent_weak = child_borrow.parent.as_ref().unwrap();
-        let parent_rc = parent_weak.upgrade().unwrap();
+        };
         {
             let parent_ref = parent_rc.as_ref();
             let mut parent_borrow = parent_ref.borrow_mut();
@@ -176,121 +277,304 @@ impl QuadTree {
         QuadTree::_actr_quad_tree_remove_tree(parent_rc, level + 1);
     }
 
+    pub fn shrink(tree_rc: Rc<RefCell<QuadTree<T, S>>>) -> bool {
+        let mut this = tree_rc.borrow_mut();
+        if !this.root {
+            return false;
+        }
+        let mut shrunk = false;
+        loop {
+            for i in 0..4 {
+                if this.branches[i].as_ref().is_some_and(|b| b.borrow().count == 0) {
+                    this.branches[i] = None;
+                }
+            }
+            if !this.items.is_empty() || !this.stuck.is_empty() {
+                return shrunk;
+            }
+
+            let mut branches = this.branches.iter().flatten();
+            let child_rc = match (branches.next(), branches.next()) {
+                (Some(child), None) => child.clone(),
+                (Some(_), Some(_)) => {
+                    if !QuadTree::shrink_grown(&mut this, &tree_rc) {
+                        return shrunk;
+                    }
+                    shrunk = true;
+                    continue;
+                }
+                _ => return shrunk,
+            };
+
+            // the root keeps its identity and takes over the only child's bounds and contents
+            let mut child = child_rc.borrow_mut();
+            this.bounds = child.bounds;
+            this.items = mem::take(&mut child.items);
+            this.stuck = mem::take(&mut child.stuck);
+            this.branches = mem::replace(&mut child.branches, (0..4).map(|_| None).collect());
+            child.count = 0;
+            drop(child);
+
+            let this = &mut *this;
+            for leaf in this.items.iter().chain(this.stuck.iter()) {
+                leaf.set_parent(Rc::downgrade(&tree_rc));
+            }
+            for branch in this.branches.iter().flatten() {
+                branch.borrow_mut().parent = Some(Rc::downgrade(&tree_rc));
+            }
+            shrunk = true;
+        }
+    }
+
+    // undoes one grow, every branch must only wrap the quadrant that faces the centre
+    fn shrink_grown(this: &mut QuadTree<T, S>, tree_rc: &Rc<RefCell<QuadTree<T, S>>>) -> bool {
+        let size = this.bounds.w.half();
+        let half = size.half();
+        let inner = QuadTreeBounds::new(this.bounds.x + half, this.bounds.y + half, size, size);
+
+        let mut inner_branches = Vec::with_capacity(4);
+        for i in 0..4 {
+            let Some(branch) = &this.branches[i] else {
+                inner_branches.push(None);
+                continue;
+            };
+            let wrapper = branch.borrow();
+            if !wrapper.items.is_empty() || !wrapper.stuck.is_empty() {
+                return false;
+            }
+            let mut wrapped = wrapper
+                .branches
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| b.as_ref().is_some_and(|b| b.borrow().count > 0));
+            let child = match (wrapped.next(), wrapped.next()) {
+                (Some((j, child)), None) if j == (i + 2) % 4 => child.clone().unwrap(),
+                _ => return false,
+            };
+            let expected = Self::quadrant(&inner, i, this.orientation());
+            let bounds = child.borrow().bounds;
+            if bounds.x != expected.x
+                || bounds.y != expected.y
+                || bounds.w != expected.w
+                || bounds.h != expected.h
+            {
+                return false;
+            }
+            inner_branches.push(Some(child));
+        }
+
+        this.bounds = inner;
+        this.branches = inner_branches;
+        for branch in this.branches.iter().flatten() {
+            branch.borrow_mut().parent = Some(Rc::downgrade(tree_rc));
+        }
+        true
+    }
+
     pub fn query(
-        self_rc: Rc<RefCell<QuadTree>>,
-        area: QuadTreeBounds,
-        results: &mut Vec<QuadTreeLeaf>,
-    ) {
+        self_rc: Rc<RefCell<QuadTree<T, S>>>,
+        area: QuadTreeBounds<S>,
+        results: &mut Vec<QuadTreeLeaf<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        area.validate()?;
+        QuadTree::query_where(self_rc, |bounds| area.intersects(bounds), results);
+        Ok(())
+    }
+
+    pub fn query_point(
+        self_rc: Rc<RefCell<QuadTree<T, S>>>,
+        x: S,
+        y: S,
+        results: &mut Vec<QuadTreeLeaf<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        if !x.to_f64().is_finite() || !y.to_f64().is_finite() {
+            return Err(QuadTreeError::InvalidBounds);
+        }
+        QuadTree::query_where(self_rc, |bounds| bounds.contains_point(x, y), results);
+        Ok(())
+    }
+
+    pub fn query_circle(
+        self_rc: Rc<RefCell<QuadTree<T, S>>>,
+        x: S,
+        y: S,
+        radius: S,
+        results: &mut Vec<QuadTreeLeaf<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        let valid = radius >= S::zero() && x.to_f64().is_finite() && y.to_f64().is_finite();
+        if !valid {
+            return Err(QuadTreeError::InvalidBounds);
+        }
+        QuadTree::query_where(
+            self_rc,
+            |bounds| bounds.intersects_circle(x, y, radius),
+            results,
+        );
+        Ok(())
+    }
+
+    fn query_where<F>(self_rc: Rc<RefCell<QuadTree<T, S>>>, overlaps: F, results: &mut Vec<QuadTreeLeaf<T, S>>)
+    where
+        F: Fn(&QuadTreeBounds<S>) -> bool,
+    {
         let mut list = Vec::new();
 
         let self_ref = self_rc.as_ref();
         let self_borrow = self_ref.borrow();
 
-        if area.intersects(&self_borrow.bounds) {
+        if overlaps(&self_borrow.loose_bounds()) {
             list.push(self_rc.clone());
         }
 
-        while list.len() > 0 {
-            let tree_rc = list.pop().unwrap();
+        while let Some(tree_rc) = list.pop() {
             let tree_ref = tree_rc.as_ref();
             let tree_borrow = tree_ref.borrow();
 
-            for i in 0..4 {
-                if tree_borrow.branches[i].is_none() {
-                    continue;
-                }
-
-                let branch_option = tree_borrow.branches[i].as_ref();
-                let branch_rc = branch_option.unwrap();
-                let branch_ref = branch_rc.as_ref();
-                let branch = branch_ref.borrow();
-
-                if area.intersects(&branch.bounds) {
+            for branch_rc in tree_borrow.branches.iter().flatten() {
+                if overlaps(&branch_rc.borrow().loose_bounds()) {
                     list.push(branch_rc.clone());
                 }
             }
             for leaf in tree_borrow.items.iter() {
-                if area.intersects(&leaf.bounds) {
-                    //QuadTree::log(format!("area {} intersects {}", area, &leaf.bounds));
+                if overlaps(&leaf.bounds) {
                     results.push(leaf.clone());
                 }
             }
 
             for leaf in tree_borrow.stuck.iter() {
-                if area.intersects(&leaf.bounds) {
-                    //QuadTree::log(format!("area {} intersects {}", area, &leaf.bounds));
+                if overlaps(&leaf.bounds) {
                     results.push(leaf.clone());
                 }
             }
         }
     }
 
-    fn grow(tree: Rc<RefCell<QuadTree>>) {
+    pub fn leaves(self_rc: Rc<RefCell<QuadTree<T, S>>>) -> QuadTreeLeaves<T, S> {
+        QuadTreeLeaves {
+            stack: vec![self_rc],
+            current: Vec::new().into_iter(),
+        }
+    }
+
+    pub fn nodes(self_rc: Rc<RefCell<QuadTree<T, S>>>) -> QuadTreeNodes<T, S> {
+        QuadTreeNodes {
+            stack: vec![(self_rc, 0)],
+        }
+    }
+
+    fn info(&self, depth: usize) -> QuadTreeNodeInfo<QuadTreeBounds<S>> {
+        QuadTreeNodeInfo {
+            depth,
+            bounds: self.bounds,
+            items: self.items.len(),
+            stuck: self.stuck.len(),
+            count: self.count,
+        }
+    }
+
+    fn adopt(tree_rc: &Rc<RefCell<QuadTree<T, S>>>, index: usize) {
+        let this = tree_rc.borrow();
+        if let Some(branch) = &this.branches[index] {
+            branch.borrow_mut().parent = Some(Rc::downgrade(tree_rc));
+        }
+    }
+
+    fn grow(tree: Rc<RefCell<QuadTree<T, S>>>) -> Result<(), QuadTreeError> {
         let mut this = tree.borrow_mut();
 
         let size = this.bounds.w;
-        let half = size / 2;
-        if this.branches[0].is_some() {
-            let mut new_tree = QuadTree::new(
-                false,
-                this.bounds.x - half,
-                this.bounds.y - half,
-                size,
-                Some(Rc::downgrade(&tree)),
-            );
-            new_tree.branches[2] = mem::replace(&mut this.branches[0], None);
-            this.branches[0] = Some(Rc::new(RefCell::new(new_tree)));
+        if size <= S::zero() {
+            // a negative root never gets any bigger, an empty one is sized by its first insert instead
+            return Err(QuadTreeError::InvalidBounds);
         }
-        if this.branches[1].is_some() {
-            let mut new_tree = QuadTree::new(
-                false,
-                this.bounds.x + half,
-                this.bounds.y - half,
-                size,
-                Some(Rc::downgrade(&tree)),
-            );
-            new_tree.branches[3] = mem::replace(&mut this.branches[1], None);
-            this.branches[1] = Some(Rc::new(RefCell::new(new_tree)));
+        let half = size.half();
+        let grown = match (
+            this.bounds.x.checked_sub(half),
+            this.bounds.y.checked_sub(half),
+            this.bounds.w.checked_add(size),
+            this.bounds.h.checked_add(size),
+        ) {
+            (Some(x), Some(y), Some(w), Some(h)) => QuadTreeBounds::new(x, y, w, h),
+            _ => return Err(QuadTreeError::OutOfRange),
+        };
+        if grown.checked_right().is_none() || grown.checked_bottom().is_none() {
+            return Err(QuadTreeError::OutOfRange);
         }
-        if this.branches[2].is_some() {
-            let mut new_tree = QuadTree::new(
-                false,
-                this.bounds.x + half,
-                this.bounds.y + half,
-                size,
-                Some(Rc::downgrade(&tree)),
-            );
-            new_tree.branches[0] = mem::replace(&mut this.branches[2], None);
-            this.branches[2] = Some(Rc::new(RefCell::new(new_tree)));
-        }
-        if this.branches[3].is_some() {
-            let mut new_tree = QuadTree::new(
+        // each branch is wrapped by a node of the old size on its own side, and sits in its opposite quadrant
+        for i in 0..4 {
+            if this.branches[i].is_none() {
+                continue;
+            }
+            let (right, high_y) = this.orientation().halves(i);
+            let mut new_tree = QuadTree::with_config(
                 false,
-                this.bounds.x - half,
-                this.bounds.y + half,
+                if right { this.bounds.x + half } else { this.bounds.x - half },
+                if high_y { this.bounds.y + half } else { this.bounds.y - half },
                 size,
                 Some(Rc::downgrade(&tree)),
+                this.config,
             );
-            new_tree.branches[1] = mem::replace(&mut this.branches[3], None);
-            this.branches[3] = Some(Rc::new(RefCell::new(new_tree)));
+            new_tree.count = this.branches[i].as_ref().unwrap().borrow().count;
+            new_tree.branches[(i + 2) % 4] = mem::replace(&mut this.branches[i], None);
+            let wrapper = Rc::new(RefCell::new(new_tree));
+            QuadTree::adopt(&wrapper, (i + 2) % 4);
+            this.branches[i] = Some(wrapper);
         }
 
-        this.bounds.x -= half;
-        this.bounds.y -= half;
-        this.bounds.w += size;
-        this.bounds.h += size;
+        this.bounds = grown;
+        Ok(())
+    }
+
+    // twice the leaf's extent, or the min size, so the new root can split at least once
+    fn size_to(&mut self, bounds: &QuadTreeBounds<S>) -> Result<(), QuadTreeError> {
+        let extent = max(max(bounds.w, bounds.h), self.config.min_size);
+        let sized = match (
+            (bounds.x + bounds.w.half()).checked_sub(extent),
+            (bounds.y + bounds.h.half()).checked_sub(extent),
+            extent.checked_add(extent),
+        ) {
+            (Some(x), Some(y), Some(size)) => QuadTreeBounds::new(x, y, size, size),
+            _ => return Err(QuadTreeError::OutOfRange),
+        };
+        if sized.checked_right().is_none() || sized.checked_bottom().is_none() {
+            return Err(QuadTreeError::OutOfRange);
+        }
+        self.bounds = sized;
+        Ok(())
     }
 
     pub fn insert(
-        tree_rc: Rc<RefCell<QuadTree>>,
-        item: i64,
-        bounds: QuadTreeBounds,
+        tree_rc: Rc<RefCell<QuadTree<T, S>>>,
+        identity: i64,
+        item: T,
+        bounds: QuadTreeBounds<S>,
         level: usize,
-    ) {
+    ) -> Result<(), QuadTreeError> {
+        QuadTree::insert_leaf(tree_rc, QuadTreeLeaf::new(identity, item, bounds, Weak::new()), level)
+    }
+
+    // a leaf pushed down a level is the same leaf, so copies of it keep following it
+    fn insert_leaf(
+        tree_rc: Rc<RefCell<QuadTree<T, S>>>,
+        new_leaf: QuadTreeLeaf<T, S>,
+        level: usize,
+    ) -> Result<(), QuadTreeError> {
         //QuadTree::log(format!("inserting item {item} {bounds} level: {level}"));
+        let bounds = new_leaf.bounds;
+        bounds.validate()?;
+        if bounds.checked_right().is_none() || bounds.checked_bottom().is_none() {
+            return Err(QuadTreeError::OutOfRange);
+        }
 
         let tree_ref = tree_rc.as_ref();
 
+        {
+            let mut this = tree_ref.borrow_mut();
+            if this.root && this.count == 0 && this.bounds.w == S::zero() {
+                this.size_to(&bounds)?;
+            }
+        }
+
         let this = tree_ref.borrow();
 
         if this.root {
@@ -299,7 +583,7 @@ impl QuadTree {
                 drop(this);
                 loop {
                     //QuadTree::log(format!("growing level: {level}"));
-                    QuadTree::grow(tree_rc.clone());
+                    QuadTree::grow(tree_rc.clone())?;
                     let this = tree_ref.borrow();
                     if this.bounds.contains(&bounds) {
                         drop(this);
@@ -316,13 +600,13 @@ impl QuadTree {
 
         let mut this = tree_ref.borrow_mut();
 
-        let new_leaf = QuadTreeLeaf::new(item, bounds, Rc::downgrade(&tree_rc));
-        
+        new_leaf.set_parent(Rc::downgrade(&tree_rc));
 
-        this.items.push(new_leaf);
-        if this.items.len() < 2 {
+        this.count += 1;
+        QuadTree::push_leaf(&mut this.items, new_leaf);
+        if this.items.len() <= this.config.capacity {
             //QuadTree::log(format!("small list level: {level}"));
-            return;
+            return Ok(());
         } else {
             //QuadTree::log(format!("big list level: {level}"));
         }
@@ -331,45 +615,110 @@ impl QuadTree {
 
         while this.items.len() > 0 {
             //QuadTree::log(format!("working item level: {level}"));
-            let mut leaf = this.items.pop().unwrap();
+            let leaf = this.items.pop().unwrap();
 
             let index = this.index(&leaf.bounds);
+            // odd integer sizes leave a sliver the quadrants don't cover, anything on it stays here
+            let fits = index >= 0
+                && match &this.branches[index as usize] {
+                    Some(branch) => branch.borrow().loose_bounds().contains(&leaf.bounds),
+                    None => {
+                        let quadrant = Self::quadrant(&this.bounds, index as usize, this.orientation());
+                        quadrant.expand(this.config.margin(quadrant.w)).contains(&leaf.bounds)
+                    }
+                };
 
-            if index < 0 || this.bounds.w < 16 {
-                leaf.parent = Rc::downgrade(&tree_rc);
-                this.stuck.push(leaf);
+            if !fits || !this.config.can_split(this.bounds.w, level) {
+                leaf.set_parent(Rc::downgrade(&tree_rc));
+                QuadTree::push_leaf(&mut this.stuck, leaf);
                 continue;
             }
 
             if this.branches[index as usize].is_none() {
                 //QuadTree::log(format!("new tree level: {level}"));
-                let size = this.bounds.w / 2;
-                let mut x = this.bounds.x;
-                let mut y = this.bounds.y;
-
-                if 1 == index {
-                    x += size;
-                } else if 2 == index {
-                    x += size;
-                    y += size;
-                } else if 3 == index {
-                    y += size;
-                }
-
-                this.branches[index as usize] = Some(Rc::new(RefCell::new(QuadTree::new(
+                let quadrant = Self::quadrant(&this.bounds, index as usize, this.orientation());
+                this.branches[index as usize] = Some(Rc::new(RefCell::new(QuadTree::with_config(
                     false,
-                    x,
-                    y,
-                    size,
+                    quadrant.x,
+                    quadrant.y,
+                    quadrant.w,
                     Some(Rc::downgrade(&tree_rc)),
+                    this.config,
                 ))));
             }
 
             let foo = &this.branches[index as usize];
             let bar = foo.clone().unwrap();
 
-            QuadTree::insert(bar, leaf.identity, leaf.bounds, level + 1);
+            QuadTree::insert_leaf(bar, leaf, level + 1)?;
         }
         //QuadTree::log(format!("done inserting level: {level}"));
+        Ok(())
+    }
+}
+
+// depth first, each node is only borrowed while its leaves are copied out so the tree can change between calls
+pub struct QuadTreeLeaves<T, S = i64>
+where
+    T: Clone,
+    S: QuadTreeScalar,
+{
+    stack: Vec<Rc<RefCell<QuadTree<T, S>>>>,
+    current: IntoIter<QuadTreeLeaf<T, S>>,
+}
+
+impl<T, S> Iterator for QuadTreeLeaves<T, S>
+where
+    T: Clone,
+    S: QuadTreeScalar,
+{
+    type Item = QuadTreeLeaf<T, S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(leaf) = self.current.next() {
+                return Some(leaf);
+            }
+            let tree_rc = self.stack.pop()?;
+            let tree = tree_rc.borrow();
+            // reversed so quadrant 0 comes off the stack first
+            self.stack.extend(tree.branches.iter().rev().flatten().cloned());
+            self.current = tree
+                .items
+                .iter()
+                .chain(tree.stuck.iter())
+                .cloned()
+                .collect::<Vec<_>>()
+                .into_iter();
+        }
+    }
+}
+
+// nodes still to visit, each with its depth
+type QuadTreeNodeStack<T, S> = Vec<(Rc<RefCell<QuadTree<T, S>>>, usize)>;
+
+// depth first, borrowing one node at a time like QuadTreeLeaves
+pub struct QuadTreeNodes<T, S = i64>
+where
+    T: Clone,
+    S: QuadTreeScalar,
+{
+    stack: QuadTreeNodeStack<T, S>,
+}
+
+impl<T, S> Iterator for QuadTreeNodes<T, S>
+where
+    T: Clone,
+    S: QuadTreeScalar,
+{
+    type Item = QuadTreeNodeInfo<QuadTreeBounds<S>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (tree_rc, depth) = self.stack.pop()?;
+        let tree = tree_rc.borrow();
+        for branch in tree.branches.iter().rev().flatten() {
+            self.stack.push((branch.clone(), depth + 1));
+        }
+        Some(tree.info(depth))
     }
 }
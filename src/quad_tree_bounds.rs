@@ -1,20 +1,25 @@
 use std::fmt::Display;
 
-pub struct QuadTreeBounds {
-    pub x: i64,
-    pub y: i64,
-    pub w: i64,
-    pub h: i64,
+use crate::{
+    quad_tree_error::QuadTreeError,
+    quad_tree_scalar::{QuadTreeScalar, max, min},
+};
+
+pub struct QuadTreeBounds<S = i64> {
+    pub x: S,
+    pub y: S,
+    pub w: S,
+    pub h: S,
 }
-impl Clone for QuadTreeBounds {
+impl<S: QuadTreeScalar> Clone for QuadTreeBounds<S> {
     fn clone(&self) -> Self {
         Self { x: self.x.clone(), y: self.y.clone(), w: self.w.clone(), h: self.h.clone() }
     }
 }
-impl Copy for QuadTreeBounds {
+impl<S: QuadTreeScalar> Copy for QuadTreeBounds<S> {
 
 }
-impl Display for QuadTreeBounds {
+impl<S: QuadTreeScalar> Display for QuadTreeBounds<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_str("qtb x: ").unwrap();
         f.write_str(&self.x.to_string()).unwrap();
@@ -28,34 +33,85 @@ impl Display for QuadTreeBounds {
 }
 
 
-impl QuadTreeBounds {
-    pub fn new(x: i64, y: i64, w: i64, h: i64) -> Self {
+impl<S: QuadTreeScalar> QuadTreeBounds<S> {
+    pub fn new(x: S, y: S, w: S, h: S) -> Self {
         Self { x, y, w, h }
     }
-    pub fn contains(&self, other: &QuadTreeBounds) -> bool {
+    // the corners can come in any order
+    pub fn from_corners(x0: S, y0: S, x1: S, y1: S) -> Result<Self, QuadTreeError> {
+        let (x, y) = (min(x0, x1), min(y0, y1));
+        match (max(x0, x1).checked_sub(x), max(y0, y1).checked_sub(y)) {
+            (Some(w), Some(h)) => Ok(Self::new(x, y, w, h)),
+            _ => Err(QuadTreeError::OutOfRange),
+        }
+    }
+    // the extent has to be above zero, which nan never is
+    pub fn validate(&self) -> Result<(), QuadTreeError> {
+        let zero = S::zero();
+        let extent = self.w > zero && self.h > zero;
+        let finite = self.x.to_f64().is_finite() && self.y.to_f64().is_finite();
+        if extent && finite { Ok(()) } else { Err(QuadTreeError::InvalidBounds) }
+    }
+    pub fn right(&self) -> S {
+        self.x.saturating_add(self.w)
+    }
+    pub fn bottom(&self) -> S {
+        self.y.saturating_add(self.h)
+    }
+    pub fn checked_right(&self) -> Option<S> {
+        self.x.checked_add(self.w)
+    }
+    pub fn checked_bottom(&self) -> Option<S> {
+        self.y.checked_add(self.h)
+    }
+    pub fn contains(&self, other: &QuadTreeBounds<S>) -> bool {
         self.x <= other.x
-            && self.x + self.w >= other.x + other.w
+            && self.right() >= other.right()
             && self.y <= other.y
-            && self.y + self.h >= other.y + other.h
+            && self.bottom() >= other.bottom()
     }
-    pub fn intersects(&self, other: &QuadTreeBounds) -> bool {
+    pub fn expand(&self, margin: S) -> Self {
+        Self::new(
+            self.x.saturating_sub(margin),
+            self.y.saturating_sub(margin),
+            self.w.saturating_add(margin).saturating_add(margin),
+            self.h.saturating_add(margin).saturating_add(margin),
+        )
+    }
+
+    pub fn contains_point(&self, x: S, y: S) -> bool {
+        self.x <= x && x < self.right() && self.y <= y && y < self.bottom()
+    }
+
+    pub fn intersects_circle(&self, x: S, y: S, radius: S) -> bool {
+        let (x, y, radius, zero) = (x.widen(), y.widen(), radius.widen(), S::zero().widen());
+        let dx = max(max(self.x.widen() - x, x - self.right().widen()), zero);
+        let dy = max(max(self.y.widen() - y, y - self.bottom().widen()), zero);
+        // a box that far out on either axis is missed, and stopping here keeps the squares below from overflowing
+        if dx >= radius || dy >= radius {
+            return false;
+        }
+        dx * dx + dy * dy < radius * radius
+    }
+
+    pub fn intersects(&self, other: &QuadTreeBounds<S>) -> bool {
         /*
             RectA.Left < RectB.Right &&
             RectA.Right > RectB.Left &&
             RectA.Top > RectB.Bottom &&
             RectA.Bottom < RectB.Top
         */
-        if self.x >= other.x + other.w {
+        if self.x >= other.right() {
             return false;
         }
 
-        if other.x >= self.x + self.w {
+        if other.x >= self.right() {
             return false;
         }
-        if self.y >= other.y + other.h {
+        if self.y >= other.bottom() {
             return false;
         }
-        if other.y >= self.y + self.h {
+        if other.y >= self.bottom() {
             return false;
         }
         true
@@ -1,59 +1,154 @@
 use std::fmt::Display;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
+use crate::{
+    quad_tree_error::QuadTreeError,
+    quad_tree_scalar::{QuadTreeScalar, max, min},
+};
 
-#[derive(Copy, Deserialize)]
-pub struct QuadTreeBoundsTs {
-    pub x: i64,
-    pub y: i64,
-    pub w: i64,
-    pub h: i64,
+
+#[derive(Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct QuadTreeBoundsTs<S = i64> {
+    pub x: S,
+    pub y: S,
+    pub w: S,
+    pub h: S,
 }
 
-impl Clone for QuadTreeBoundsTs {
+impl<S> Clone for QuadTreeBoundsTs<S>
+where
+    S: Clone,
+{
     fn clone(&self) -> Self {
         Self {
-            x: self.x,
-            y: self.y,
-            w: self.w,
-            h: self.h,
+            x: self.x.clone(),
+            y: self.y.clone(),
+            w: self.w.clone(),
+            h: self.h.clone(),
         }
     }
 }
 
-impl Display for QuadTreeBoundsTs {
+impl<S> Display for QuadTreeBoundsTs<S>
+where
+    S: QuadTreeScalar,
+{
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("qtb top: {}, right: {}, bottom: {}, left: {}, width: {}, height: {}", self.top(), self.right(), self.bottom(), self.left(), self.w, self.h))
     }
 }
 
-impl QuadTreeBoundsTs {
-    pub fn new(x: i64, y: i64, w: i64, h: i64) -> Self {
+impl<S> QuadTreeBoundsTs<S>
+where
+    S: QuadTreeScalar,
+{
+    pub fn new(x: S, y: S, w: S, h: S) -> Self {
         Self { x, y, w, h }
     }
 
-    pub fn top(&self) -> i64 {
-        self.y + self.h
+    // the corners can come in any order, the result always has a positive extent unless they share an edge
+    pub fn from_corners(x0: S, y0: S, x1: S, y1: S) -> Result<Self, QuadTreeError> {
+        let (x, y) = (min(x0, x1), min(y0, y1));
+        match (max(x0, x1).checked_sub(x), max(y0, y1).checked_sub(y)) {
+            (Some(w), Some(h)) => Ok(Self::new(x, y, w, h)),
+            _ => Err(QuadTreeError::OutOfRange),
+        }
+    }
+
+    // an empty, negative or nan extent can't be placed in a tree, nan is never above zero
+    pub fn validate(&self) -> Result<(), QuadTreeError> {
+        let zero = S::zero();
+        let extent = self.w > zero && self.h > zero;
+        let finite = self.x.to_f64().is_finite() && self.y.to_f64().is_finite();
+        if extent && finite { Ok(()) } else { Err(QuadTreeError::InvalidBounds) }
+    }
+
+    pub fn top(&self) -> S {
+        self.y.saturating_add(self.h)
     }
-    pub fn right(&self) -> i64 {
-        self.x + self.w
+    pub fn right(&self) -> S {
+        self.x.saturating_add(self.w)
     }
-    pub fn bottom(&self) -> i64 {
+    pub fn checked_top(&self) -> Option<S> {
+        self.y.checked_add(self.h)
+    }
+    pub fn checked_right(&self) -> Option<S> {
+        self.x.checked_add(self.w)
+    }
+    pub fn bottom(&self) -> S {
         self.y
     }
-    pub fn left(&self) -> i64 {
+    pub fn left(&self) -> S {
         self.x
     }
 
     pub fn center(&self) -> (f64, f64) {
         (
-            self.x as f64 + self.w as f64 * 0.5,
-            self.y as f64 + self.h as f64 * 0.5
+            self.x.to_f64() + self.w.to_f64() * 0.5,
+            self.y.to_f64() + self.h.to_f64() * 0.5
+        )
+    }
+
+    pub fn distance(&self, x: S, y: S) -> f64 {
+        let (x, y) = (x.to_f64(), y.to_f64());
+        let dx = (self.left().to_f64() - x).max(x - self.right().to_f64()).max(0.0);
+        let dy = (self.bottom().to_f64() - y).max(y - self.top().to_f64()).max(0.0);
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    // slab test, how far along the segment from (x0, y0) to (x1, y1) it first touches the box, 0 at the start and 1 at the end
+    pub fn segment_entry(&self, x0: S, y0: S, x1: S, y1: S) -> Option<f64> {
+        let (mut enter, mut exit) = (0.0f64, 1.0f64);
+        let slabs = [
+            (x0.to_f64(), x1.to_f64(), self.left().to_f64(), self.right().to_f64()),
+            (y0.to_f64(), y1.to_f64(), self.bottom().to_f64(), self.top().to_f64()),
+        ];
+        for (start, end, low, high) in slabs {
+            let delta = end - start;
+            if delta == 0.0 {
+                // parallel to this slab, it has to start inside it
+                if start < low || start > high {
+                    return None;
+                }
+                continue;
+            }
+            let (near, far) = ((low - start) / delta, (high - start) / delta);
+            enter = enter.max(near.min(far));
+            exit = exit.min(near.max(far));
+            if enter > exit {
+                return None;
+            }
+        }
+        Some(enter)
+    }
+
+    pub fn expand(&self, margin: S) -> Self {
+        Self::new(
+            self.x.saturating_sub(margin),
+            self.y.saturating_sub(margin),
+            self.w.saturating_add(margin).saturating_add(margin),
+            self.h.saturating_add(margin).saturating_add(margin),
         )
     }
 
-    pub fn contains(&self, other: QuadTreeBoundsTs) -> bool {
+    pub fn contains_point(&self, x: S, y: S) -> bool {
+        self.left() <= x && x < self.right() && self.bottom() <= y && y < self.top()
+    }
+
+    pub fn intersects_circle(&self, x: S, y: S, radius: S) -> bool {
+        // exact squared distance from the centre to the closest point of the box
+        let (x, y, radius, zero) = (x.widen(), y.widen(), radius.widen(), S::zero().widen());
+        let dx = max(max(self.left().widen() - x, x - self.right().widen()), zero);
+        let dy = max(max(self.bottom().widen() - y, y - self.top().widen()), zero);
+        // a box that far out on either axis is missed, and stopping here keeps the squares below from overflowing
+        if dx >= radius || dy >= radius {
+            return false;
+        }
+        dx * dx + dy * dy < radius * radius
+    }
+
+    pub fn contains(&self, other: QuadTreeBoundsTs<S>) -> bool {
         let result = self.top() >= other.top() &&
         self.right() >= other.right() &&
         self.bottom() <= other.bottom() &&
@@ -68,7 +163,7 @@ impl QuadTreeBoundsTs {
         result
     }
 
-    pub fn intersects(&self, other: QuadTreeBoundsTs) -> bool {
+    pub fn intersects(&self, other: QuadTreeBoundsTs<S>) -> bool {
         //println!(" * * * INTERSECT TEST * * *");
         //println!("{}", self);
         //println!("{}", other);
@@ -1,91 +1,252 @@
 use actr_task::task_manager::TaskManager;
 use parking_lot::{Mutex, RwLock};
 use std::{
+    array,
+    cmp::Ordering as CmpOrdering,
+    collections::BinaryHeap,
+    iter,
     mem,
+    ops::ControlFlow,
     sync::{
         Arc, Weak,
         atomic::{AtomicU64, Ordering},
-        mpsc::Sender,
+        mpsc::{self, Sender},
     },
+    thread,
+    vec::IntoIter,
 };
 
-use crate::{quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_leaf_ts::QuadTreeLeafTs};
+use crate::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_config::QuadTreeConfig,
+    quad_tree_error::QuadTreeError,
+    quad_tree_leaf_ts::{QuadTreeLeafPairTs, QuadTreeLeafTs},
+    quad_tree_node_info::QuadTreeNodeInfo,
+    quad_tree_orientation::QuadTreeOrientation,
+    quad_tree_polygon_ts::QuadTreePolygonTs,
+    quad_tree_scalar::{QuadTreeScalar, max, min},
+    quad_tree_snapshot_ts::{QuadTreeLeafSnapshotTs, QuadTreeNodeSnapshotTs, QuadTreeSnapshotTs},
+};
 
 
 static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+// the parallel bulk loader builds the two levels below the root on their own threads
+const BULK_PARALLEL_DEPTH: usize = 2;
+
+// one slot per quadrant, empty until something sinks into it
+type QuadTreeBranchesTs<T, S> = Vec<Option<Arc<RwLock<QuadTreeBranchTs<T, S>>>>>;
 
-pub struct QuadTreeBranchTs<T>
+pub struct QuadTreeBranchTs<T, S = i64>
 where
     T: Clone + Send + Sync + 'static,
+    S: QuadTreeScalar,
 {
     identity: u64,
+    // the root's identity, every node under it shares it so a leaf's tree is known without walking up
+    tree: u64,
     root: bool,
-    bounds: QuadTreeBoundsTs,
-    items: Vec<QuadTreeLeafTs<T>>,
-    stuck: Vec<QuadTreeLeafTs<T>>,
-    branches: Vec<Option<Arc<RwLock<QuadTreeBranchTs<T>>>>>,
-    parent: Option<Weak<RwLock<QuadTreeBranchTs<T>>>>,
+    // set once the node is cut out of its tree, an insert that reaches it late has to start again
+    detached: bool,
+    bounds: QuadTreeBoundsTs<S>,
+    config: QuadTreeConfig<S>,
+    count: usize,
+    items: Vec<QuadTreeLeafTs<T, S>>,
+    stuck: Vec<QuadTreeLeafTs<T, S>>,
+    branches: QuadTreeBranchesTs<T, S>,
+    parent: Option<Weak<RwLock<QuadTreeBranchTs<T, S>>>>,
 }
 
-impl<T> QuadTreeBranchTs<T>
+impl<T, S> QuadTreeBranchTs<T, S>
 where
     T: Clone + Send + Sync,
+    S: QuadTreeScalar,
 {
     pub fn new(
         root: bool,
-        x: i64,
-        y: i64,
-        size: i64,
-        parent: Option<Weak<RwLock<QuadTreeBranchTs<T>>>>,
+        x: S,
+        y: S,
+        size: S,
+        parent: Option<Weak<RwLock<QuadTreeBranchTs<T, S>>>>,
     ) -> Arc<RwLock<Self>> {
         
-        Arc::new(RwLock::new(Self::new_unlocked(root, x, y, size, parent)))
+        Self::with_config(root, x, y, size, parent, QuadTreeConfig::default())
+    }
+
+    // a root with no size yet, the first insert centres it on that leaf
+    pub fn auto(config: QuadTreeConfig<S>) -> Arc<RwLock<Self>> {
+        let zero = S::zero();
+        Self::with_config(true, zero, zero, zero, None, config)
+    }
+
+    pub fn with_config(
+        root: bool,
+        x: S,
+        y: S,
+        size: S,
+        parent: Option<Weak<RwLock<QuadTreeBranchTs<T, S>>>>,
+        config: QuadTreeConfig<S>,
+    ) -> Arc<RwLock<Self>> {
+        Arc::new(RwLock::new(Self::new_unlocked(root, x, y, size, parent, config)))
     }
 
     fn new_unlocked(
         root: bool,
-        x: i64,
-        y: i64,
-        size: i64,
-        parent: Option<Weak<RwLock<QuadTreeBranchTs<T>>>>,
+        x: S,
+        y: S,
+        size: S,
+        parent: Option<Weak<RwLock<QuadTreeBranchTs<T, S>>>>,
+        config: QuadTreeConfig<S>,
     ) -> Self {
         let bounds = QuadTreeBoundsTs::new(x, y, size, size);
         //println!(" * * * NEW BRANCH {}", bounds);
+        let identity = SEQUENCE.fetch_add(1, Ordering::Relaxed);
         Self {
-            identity: SEQUENCE.fetch_add(1, Ordering::Relaxed),
+            identity,
+            tree: identity,
             root,
+            detached: false,
             bounds,
-            items: Vec::with_capacity(2),
+            config,
+            count: 0,
+            items: Vec::new(),
             stuck: Vec::new(),
             branches: (0..4).map(|_| None).collect::<Vec<_>>(),
             parent: parent,
         }
     }
 
-    fn index(&self, other: QuadTreeBoundsTs) -> isize {
+    // a fresh node hanging under this one, in the same tree
+    fn new_child(&self, arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>, x: S, y: S, size: S) -> Self {
+        let mut child = Self::new_unlocked(false, x, y, size, Some(Arc::downgrade(arc)), self.config);
+        child.tree = self.tree;
+        child
+    }
+
+    // the area this node's subtree can reach, wider than its bounds in loose mode
+    pub fn loose_bounds(&self) -> QuadTreeBoundsTs<S> {
+        self.bounds.expand(self.config.margin(self.bounds.w))
+    }
+
+    fn orientation(&self) -> QuadTreeOrientation {
+        self.config.orientation.unwrap_or(QuadTreeOrientation::YUp)
+    }
+
+    fn quadrant(
+        bounds: QuadTreeBoundsTs<S>,
+        index: usize,
+        orientation: QuadTreeOrientation,
+    ) -> QuadTreeBoundsTs<S> {
+        let size = bounds.w.half();
+        let mut x = bounds.x;
+        let mut y = bounds.y;
+
         // 0 1
         // 3 2
+        let (right, high_y) = orientation.halves(index);
+        if right {
+            x = x + size;
+        }
+        if high_y {
+            y = y + size;
+        }
+        QuadTreeBoundsTs::new(x, y, size, size)
+    }
+
+    // the branch a leaf can sink into, odd integer sizes leave a sliver the quadrants don't cover
+    fn target(&self, bounds: QuadTreeBoundsTs<S>) -> Option<usize> {
+        let index = self.index(bounds);
+        if index < 0 {
+            return None;
+        }
+        let index = index as usize;
+        let reach = match &self.branches[index] {
+            Some(branch) => branch.read().loose_bounds(),
+            None => {
+                let quadrant = Self::quadrant(self.bounds, index, self.orientation());
+                quadrant.expand(self.config.margin(quadrant.w))
+            }
+        };
+        if reach.contains(bounds) { Some(index) } else { None }
+    }
 
-        let xmid = self.bounds.x + (self.bounds.w / 2);
-        let ymid = self.bounds.y + (self.bounds.h / 2);
+    fn index(&self, other: QuadTreeBoundsTs<S>) -> isize {
+        // 0 1
+        // 3 2
+
+        let xmid = self.bounds.x + self.bounds.w.half();
+        let ymid = self.bounds.y + self.bounds.h.half();
         
+        let orientation = self.orientation();
+        if self.config.is_loose() {
+            // loose children are picked by the centre, the caller checks the leaf fits the loose bounds
+            let x = other.x + other.w.half();
+            let y = other.y + other.h.half();
+            return orientation.index(x >= xmid, y >= ymid) as isize;
+        }
 
-        if other.bottom() >= ymid {
-            if other.right() <= xmid {
-                return 0;
-            }
-            if other.left() >= xmid {
-                return 1;
-            }
+        // a leaf sitting on a midpoint goes to the high side, the same as contains_point
+        let right = if other.left() >= xmid {
+            true
+        } else if other.right() <= xmid {
+            false
+        } else {
+            return -1;
+        };
+        let high_y = if other.bottom() >= ymid {
+            true
         } else if other.top() <= ymid {
-            if other.right() <= xmid {
-                return 3;
-            }
-            if other.left() >= xmid {
-                return 2;
-            }
+            false
+        } else {
+            return -1;
+        };
+        orientation.index(right, high_y) as isize
+    }
+
+    pub fn get_bounds(&self) -> QuadTreeBoundsTs<S> {
+        self.bounds
+    }
+
+    pub fn get_config(&self) -> QuadTreeConfig<S> {
+        self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn branch_lens(&self) -> [usize; 4] {
+        array::from_fn(|i| self.branches[i].as_ref().map_or(0, |branch| branch.read().count))
+    }
+
+    fn ancestors(arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>) -> Vec<Arc<RwLock<QuadTreeBranchTs<T, S>>>> {
+        // one lock at a time going up, inserts lock going down
+        let mut list = Vec::new();
+        let mut parent = arc.read().parent.clone();
+        while let Some(next) = parent.and_then(|p| p.upgrade()) {
+            parent = next.read().parent.clone();
+            list.push(next);
+        }
+        list
+    }
+
+    pub(crate) fn same_tree(
+        a: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        b: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+    ) -> bool {
+        let tree = a.read().tree;
+        tree == b.read().tree
+    }
+
+    // takes one item off every ancestor's count
+    // the list is read before the leaf comes out, once it is out its node can be cut off from the root
+    fn leave(ancestors: &[Arc<RwLock<QuadTreeBranchTs<T, S>>>]) {
+        for ancestor in ancestors.iter() {
+            ancestor.write().count -= 1;
         }
-        return -1;
     }
 
     pub fn clear(&mut self) {
@@ -94,69 +255,41 @@ where
         }
         self.items.clear();
         self.stuck.clear();
+        self.count = 0;
     }
 
-    pub fn remove(mut leaf: &QuadTreeLeafTs<T>) -> bool {
-        let strong = leaf.get_parent();
-        if strong.is_none() {
-            return false;
-        }
-
-        let parent_mutex = strong.unwrap().clone();
+    pub fn remove(leaf: &QuadTreeLeafTs<T, S>) -> Result<(), QuadTreeError> {
+        let parent_mutex = leaf.get_parent().ok_or(QuadTreeError::Detached)?;
+        let ancestors = QuadTreeBranchTs::ancestors(&parent_mutex);
         let mut parent = parent_mutex.write();
+        if !QuadTreeBranchTs::holds(leaf, &parent_mutex) {
+            drop(parent);
+            return QuadTreeBranchTs::remove(leaf);
+        }
 
+        match parent.find_leaf(leaf) {
+            Some((false, slot)) => QuadTreeBranchTs::take_leaf(&mut parent.items, slot),
+            Some((true, slot)) => QuadTreeBranchTs::take_leaf(&mut parent.stuck, slot),
+            None => return Err(QuadTreeError::NotFound),
+        };
         leaf.set_parent(None);
-    
-
-        let mut result = false;
-        let mut item_count = 0;
-        parent.items.retain(|l| {
-            let retain = l.identity != leaf.identity;
-            if retain {
-                item_count += 1;
-            } else {
-                result = true;
-            }
-            retain
-        });
-
-        parent.stuck.retain(|l| {
-            let retain = l.identity != leaf.identity;
-            if retain {
-                item_count += 1;
-            } else {
-                result = true;
-            }
-            retain
-        });
+        let item_count = parent.items.len() + parent.stuck.len();
+        parent.count -= 1;
+        let auto_shrink = parent.config.auto_shrink;
+        drop(parent);
 
-        if item_count > 0 {
-            return result;
+        QuadTreeBranchTs::leave(&ancestors);
+        if item_count == 0 {
+            QuadTreeBranchTs::prune(&parent_mutex);
         }
-
-        for branch in parent.branches.iter() {
-            if branch.is_some() {
-                return result;
-            }
+        if auto_shrink {
+            QuadTreeBranchTs::shrink(ancestors.last().unwrap_or(&parent_mutex));
         }
-        if parent.parent.is_none() {
-            return result;
-        }
-
-        let strong = parent.parent.clone().unwrap().upgrade();
-        if strong.is_none() {
-            return result;
-        }
-        let next = strong.unwrap();
-        let identity = parent.identity;
-        drop(parent);
-
-        Self::remove_child(next, identity, 0);
-        return result;
+        Ok(())
     }
 
     fn remove_child(
-        self_pointer: Arc<RwLock<QuadTreeBranchTs<T>>>,
+        self_pointer: Arc<RwLock<QuadTreeBranchTs<T, S>>>,
         child_identity: u64,
         level: usize,
     ) {
@@ -170,8 +303,10 @@ where
 
             let branch_option = this.branches[i].clone();
             let branch_result = branch_option.unwrap();
-            let branch = branch_result.read();
-            if branch.identity == child_identity {
+            let mut branch = branch_result.write();
+            // prune let go of the child before getting here, another thread may have filled it since
+            if branch.identity == child_identity && branch.is_bare() {
+                branch.detached = true;
                 this.branches[i] = None;
             } else {
                 branch_count += 1;
@@ -200,7 +335,297 @@ where
         QuadTreeBranchTs::remove_child(next, identity, level + 1);
     }
 
-    pub fn climb(arc: Arc<RwLock<QuadTreeBranchTs<T>>>, list: &mut Vec<QuadTreeBoundsTs>) {
+    pub fn shrink(arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>) -> bool {
+        let mut this = arc.write();
+        if !this.root {
+            return false;
+        }
+        this.shrink_locked(arc)
+    }
+
+    fn shrink_locked(&mut self, arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>) -> bool {
+        let mut shrunk = false;
+        loop {
+            for i in 0..4 {
+                let Some(branch_arc) = self.branches[i].clone() else {
+                    continue;
+                };
+                // counted under the branch's own lock, a move can insert straight into it without the root
+                let mut branch = branch_arc.write();
+                if branch.count == 0 {
+                    branch.detach();
+                    self.branches[i] = None;
+                }
+            }
+            if !self.items.is_empty() || !self.stuck.is_empty() {
+                return shrunk;
+            }
+
+            let mut branches = self.branches.iter().flatten();
+            let child_arc = match (branches.next(), branches.next()) {
+                (Some(child), None) => child.clone(),
+                (Some(_), Some(_)) => {
+                    if !self.shrink_grown(arc) {
+                        return shrunk;
+                    }
+                    shrunk = true;
+                    continue;
+                }
+                _ => return shrunk,
+            };
+
+            // the root keeps its identity and takes over the only child's bounds and contents
+            let mut child = child_arc.write();
+            self.bounds = child.bounds;
+            self.items = mem::take(&mut child.items);
+            self.stuck = mem::take(&mut child.stuck);
+            self.branches = mem::replace(&mut child.branches, (0..4).map(|_| None).collect());
+            child.count = 0;
+            child.detached = true;
+            drop(child);
+
+            for leaf in self.items.iter().chain(self.stuck.iter()) {
+                leaf.set_parent(Some(Arc::downgrade(arc)));
+            }
+            for branch in self.branches.iter().flatten() {
+                branch.write().parent = Some(Arc::downgrade(arc));
+            }
+            shrunk = true;
+        }
+    }
+
+    // a whole subtree dropped from the tree, nothing in it can take leaves anymore
+    fn detach(&mut self) {
+        self.detached = true;
+        for branch in self.branches.iter().flatten() {
+            branch.write().detach();
+        }
+    }
+
+    // undoes one grow, every branch must only wrap the quadrant that faces the centre
+    fn shrink_grown(&mut self, arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>) -> bool {
+        let size = self.bounds.w.half();
+        let half = size.half();
+        let inner = QuadTreeBoundsTs::new(self.bounds.x + half, self.bounds.y + half, size, size);
+
+        // the wrappers stay locked until they are cut off, so nothing lands in them after the check
+        let wrappers = self.branches.clone();
+        let mut locked = Vec::with_capacity(4);
+        let mut empty = Vec::new();
+        let mut inner_branches = Vec::with_capacity(4);
+        for (i, branch) in wrappers.iter().enumerate() {
+            let Some(branch) = branch else {
+                inner_branches.push(None);
+                continue;
+            };
+            let wrapper = branch.write();
+            if !wrapper.items.is_empty() || !wrapper.stuck.is_empty() {
+                return false;
+            }
+            let mut child = None;
+            for (j, b) in wrapper.branches.iter().enumerate() {
+                let Some(b) = b else {
+                    continue;
+                };
+                if b.read().count == 0 {
+                    empty.push(b.clone());
+                } else if j == (i + 2) % 4 {
+                    child = Some(b.clone());
+                } else {
+                    return false;
+                }
+            }
+            let Some(child) = child else {
+                return false;
+            };
+            if child.read().bounds != Self::quadrant(inner, i, self.orientation()) {
+                return false;
+            }
+            inner_branches.push(Some(child));
+            locked.push(wrapper);
+        }
+
+        // the quadrants that go with their wrappers can still be moved into directly, so they are counted again
+        let mut dropped: Vec<_> = empty.iter().map(|b| b.write()).collect();
+        if dropped.iter().any(|b| b.count > 0) {
+            return false;
+        }
+        for branch in dropped.iter_mut() {
+            branch.detach();
+        }
+        for wrapper in locked.iter_mut() {
+            wrapper.detached = true;
+        }
+        drop(dropped);
+        drop(locked);
+
+        self.bounds = inner;
+        self.branches = inner_branches;
+        for branch in self.branches.iter().flatten() {
+            branch.write().parent = Some(Arc::downgrade(arc));
+        }
+        true
+    }
+
+    pub fn update_bounds(
+        leaf: &mut QuadTreeLeafTs<T, S>,
+        bounds: QuadTreeBoundsTs<S>,
+    ) -> Result<(), QuadTreeError> {
+        bounds.validate()?;
+        if bounds.checked_top().is_none() || bounds.checked_right().is_none() {
+            return Err(QuadTreeError::OutOfRange);
+        }
+
+        // most moves stay inside the leaf's node, those only ever lock that node
+        let parent_arc = leaf.get_parent().ok_or(QuadTreeError::Detached)?;
+        let mut parent = parent_arc.write();
+        if !QuadTreeBranchTs::holds(leaf, &parent_arc) {
+            drop(parent);
+            return QuadTreeBranchTs::update_bounds(leaf, bounds);
+        }
+        let Some((stuck, slot)) = parent.find_leaf(leaf) else {
+            return Err(QuadTreeError::NotFound);
+        };
+        if parent.loose_bounds().contains(bounds) {
+            // a stuck leaf that still straddles a midpoint stays where it is
+            let stays = !stuck || parent.index(bounds) < 0 || parent.bounds.w < parent.config.min_size;
+            if stays {
+                match stuck {
+                    false => parent.items[slot].bounds = bounds,
+                    true => parent.stuck[slot].bounds = bounds,
+                }
+                leaf.bounds = bounds;
+                return Ok(());
+            }
+        }
+        drop(parent);
+
+        // the closest node that can hold the new bounds, the root grows if it has to
+        let ancestors = QuadTreeBranchTs::ancestors(&parent_arc);
+        let root = ancestors.last().unwrap_or(&parent_arc).clone();
+        let target = iter::once(&parent_arc)
+            .chain(ancestors.iter())
+            .find(|arc| {
+                let this = arc.read();
+                this.root || this.loose_bounds().contains(bounds)
+            })
+            .unwrap_or(&root)
+            .clone();
+        // nothing is taken out until a move that climbs to the root knows the root can grow that far
+        if Arc::ptr_eq(&target, &root) {
+            let root_bounds = root.read().bounds;
+            QuadTreeBranchTs::<T, S>::reachable(root_bounds, bounds)?;
+        }
+
+        let mut parent = parent_arc.write();
+        if !QuadTreeBranchTs::holds(leaf, &parent_arc) {
+            drop(parent);
+            return QuadTreeBranchTs::update_bounds(leaf, bounds);
+        }
+        let Some((stuck, slot)) = parent.find_leaf(leaf) else {
+            return Err(QuadTreeError::NotFound);
+        };
+        let mut moved = match stuck {
+            false => QuadTreeBranchTs::take_leaf(&mut parent.items, slot),
+            true => QuadTreeBranchTs::take_leaf(&mut parent.stuck, slot),
+        };
+        let old = moved.bounds;
+        moved.bounds = bounds;
+        leaf.bounds = bounds;
+        parent.count -= 1;
+        let auto_shrink = parent.config.auto_shrink;
+        drop(parent);
+        QuadTreeBranchTs::leave(&ancestors);
+
+        let result = match QuadTreeBranchTs::reinsert(target, &root, moved.clone()) {
+            Ok(()) => Ok(()),
+            Err(error) => QuadTreeBranchTs::put_back(root.clone(), leaf, moved, old, error),
+        };
+        QuadTreeBranchTs::prune(&parent_arc);
+        if auto_shrink {
+            QuadTreeBranchTs::shrink(&root);
+        }
+        result
+    }
+
+    // the target is only locked once the leaf is out, it can be pruned or shrunk away before that
+    fn reinsert(
+        target: Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        root: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        moved: QuadTreeLeafTs<T, S>,
+    ) -> Result<(), QuadTreeError> {
+        match QuadTreeBranchTs::insert(target, moved.clone()) {
+            Err(QuadTreeError::Detached) => QuadTreeBranchTs::insert(root.clone(), moved),
+            result => result,
+        }
+    }
+
+    // the root changed under a move and could not reach the new bounds after all, the leaf goes back as it was
+    fn put_back(
+        root: Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        leaf: &mut QuadTreeLeafTs<T, S>,
+        mut moved: QuadTreeLeafTs<T, S>,
+        old: QuadTreeBoundsTs<S>,
+        error: QuadTreeError,
+    ) -> Result<(), QuadTreeError> {
+        leaf.bounds = old;
+        moved.bounds = old;
+        if QuadTreeBranchTs::insert(root, moved).is_err() {
+            leaf.set_parent(None);
+        }
+        Err(error)
+    }
+
+    // another thread can move the leaf between reading its parent and locking it, so the lock is checked after
+    fn holds(leaf: &QuadTreeLeafTs<T, S>, arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>) -> bool {
+        leaf.get_parent().is_some_and(|parent| Arc::ptr_eq(&parent, arc))
+    }
+
+    // leaves remember their slot so they can be taken back out without a scan
+    fn push_leaf(list: &mut Vec<QuadTreeLeafTs<T, S>>, leaf: QuadTreeLeafTs<T, S>) {
+        leaf.set_slot(list.len());
+        list.push(leaf);
+    }
+
+    fn take_leaf(list: &mut Vec<QuadTreeLeafTs<T, S>>, slot: usize) -> QuadTreeLeafTs<T, S> {
+        let leaf = list.swap_remove(slot);
+        if let Some(moved) = list.get(slot) {
+            moved.set_slot(slot);
+        }
+        leaf
+    }
+
+    // true when the leaf is stuck, along with its slot in that list
+    fn find_leaf(&self, leaf: &QuadTreeLeafTs<T, S>) -> Option<(bool, usize)> {
+        let slot = leaf.get_slot();
+        if self.items.get(slot).is_some_and(|l| l.identity == leaf.identity) {
+            return Some((false, slot));
+        }
+        if self.stuck.get(slot).is_some_and(|l| l.identity == leaf.identity) {
+            return Some((true, slot));
+        }
+        None
+    }
+
+    fn is_bare(&self) -> bool {
+        self.items.is_empty() && self.stuck.is_empty() && self.branches.iter().all(|b| b.is_none())
+    }
+
+    fn prune(arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>) {
+        let this = arc.read();
+        if !this.is_bare() {
+            return;
+        }
+        let identity = this.identity;
+        let next = this.parent.clone().and_then(|p| p.upgrade());
+        drop(this);
+
+        if let Some(next) = next {
+            QuadTreeBranchTs::remove_child(next, identity, 0);
+        }
+    }
+
+    pub fn climb(arc: Arc<RwLock<QuadTreeBranchTs<T, S>>>, list: &mut Vec<QuadTreeBoundsTs<S>>) {
         let this = arc.read();
         list.push(this.bounds);
         for i in 0..4 {
@@ -214,30 +639,24 @@ where
     }
 
     pub fn query(
-        arc: &Arc<RwLock<QuadTreeBranchTs<T>>>,
-        area: QuadTreeBoundsTs,
-        results: &mut Vec<QuadTreeLeafTs<T>>,
-    ) {
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        area: QuadTreeBoundsTs<S>,
+        results: &mut Vec<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        area.validate()?;
         let mut list = Vec::new();
         {
             let this = arc.read();
-            if area.intersects(this.bounds) {
+            if area.intersects(this.loose_bounds()) {
                 list.push(arc.clone());
             }
         }
-        while list.len() > 0 {
-            let arc = list.pop().unwrap();
+        while let Some(arc) = list.pop() {
             let tree = arc.read();
             //println!("items: {}, stuck:{}", tree.items.len(), tree.stuck.len());
-            for i in 0..4 {
-                if tree.branches[i].is_none() {
-                    continue;
-                }
-                let branch_option = tree.branches[i].clone().unwrap();
-                let branch = branch_option.read();
-                if area.intersects(branch.bounds) {
-                    drop(branch);
-                    list.push(branch_option);
+            for branch_arc in tree.branches.iter().flatten() {
+                if area.intersects(branch_arc.read().loose_bounds()) {
+                    list.push(branch_arc.clone());
                 }
             }
             
@@ -255,137 +674,947 @@ where
                 }
             }
         }
+        Ok(())
     }
     
-    fn grow(&mut self, zarc: Arc<RwLock<QuadTreeBranchTs<T>>>) {
+    // each top level branch is searched on its own thread and sends its matches back in one batch
+    pub fn query_parallel(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        area: QuadTreeBoundsTs<S>,
+        results: &mut Vec<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        area.validate()?;
+        let this = arc.read();
+        if !area.intersects(this.loose_bounds()) {
+            return Ok(());
+        }
+        for leaf in this.items.iter().chain(this.stuck.iter()) {
+            if area.intersects(leaf.bounds) {
+                results.push(leaf.clone());
+            }
+        }
+
+        // the branches fan out on scoped threads for now, handing them to TaskManager tasks instead
+        // waits on the requester, the actr-task API isn't part of this tree
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for branch in this.branches.iter().flatten() {
+                let sender = sender.clone();
+                scope.spawn(move || QuadTreeBranchTs::query_batch(branch, area, sender));
+            }
+            drop(sender);
+            for batch in receiver {
+                results.extend(batch);
+            }
+        });
+        Ok(())
+    }
+
+    fn query_batch(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        area: QuadTreeBoundsTs<S>,
+        sender: Sender<Vec<QuadTreeLeafTs<T, S>>>,
+    ) {
+        let mut batch = Vec::new();
+        // the area was already checked by query_parallel
+        let _ = QuadTreeBranchTs::query(arc, area, &mut batch);
+        if !batch.is_empty() {
+            // the receiver only goes away once every branch is done
+            let _ = sender.send(batch);
+        }
+    }
+
+    pub fn query_with<B, F>(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        area: QuadTreeBoundsTs<S>,
+        mut visit: F,
+    ) -> Result<ControlFlow<B>, QuadTreeError>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        area.validate()?;
+        let this = arc.read();
+        if !area.intersects(this.loose_bounds()) {
+            return Ok(ControlFlow::Continue(()));
+        }
+        Ok(this.visit(&|bounds| area.intersects(bounds), &mut visit))
+    }
+
+    // matches go out as they are found, the walk stops early once the receiver hangs up
+    pub fn query_send(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        area: QuadTreeBoundsTs<S>,
+        sender: &Sender<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        let flow = QuadTreeBranchTs::query_with(arc, area, |leaf| match sender.send(leaf.clone()) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => ControlFlow::Break(()),
+        })?;
+        match flow {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => Err(QuadTreeError::Disconnected),
+        }
+    }
+
+    pub fn query_send_batched(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        area: QuadTreeBoundsTs<S>,
+        batch_size: usize,
+        sender: &Sender<Vec<QuadTreeLeafTs<T, S>>>,
+    ) -> Result<(), QuadTreeError> {
+        let batch_size = batch_size.max(1);
+        let mut batch = Vec::with_capacity(batch_size);
+        let flow = QuadTreeBranchTs::query_with(arc, area, |leaf| {
+            batch.push(leaf.clone());
+            if batch.len() < batch_size {
+                return ControlFlow::Continue(());
+            }
+            match sender.send(mem::replace(&mut batch, Vec::with_capacity(batch_size))) {
+                Ok(()) => ControlFlow::Continue(()),
+                Err(_) => ControlFlow::Break(()),
+            }
+        })?;
+        let hung_up = flow.is_break() || (!batch.is_empty() && sender.send(batch).is_err());
+        if hung_up {
+            return Err(QuadTreeError::Disconnected);
+        }
+        Ok(())
+    }
+
+    pub fn query_point(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        x: S,
+        y: S,
+        results: &mut Vec<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        let _ = QuadTreeBranchTs::query_point_with(arc, x, y, |leaf| {
+            results.push(leaf.clone());
+            ControlFlow::<()>::Continue(())
+        })?;
+        Ok(())
+    }
+
+    pub fn query_point_with<B, F>(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        x: S,
+        y: S,
+        mut visit: F,
+    ) -> Result<ControlFlow<B>, QuadTreeError>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        if !x.to_f64().is_finite() || !y.to_f64().is_finite() {
+            return Err(QuadTreeError::InvalidBounds);
+        }
+        let this = arc.read();
+        if !this.loose_bounds().contains_point(x, y) {
+            return Ok(ControlFlow::Continue(()));
+        }
+        Ok(this.visit(&|bounds| bounds.contains_point(x, y), &mut visit))
+    }
+
+    pub fn query_circle(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        x: S,
+        y: S,
+        radius: S,
+        results: &mut Vec<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        let _ = QuadTreeBranchTs::query_circle_with(arc, x, y, radius, |leaf| {
+            results.push(leaf.clone());
+            ControlFlow::<()>::Continue(())
+        })?;
+        Ok(())
+    }
+
+    pub fn query_circle_with<B, F>(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        x: S,
+        y: S,
+        radius: S,
+        mut visit: F,
+    ) -> Result<ControlFlow<B>, QuadTreeError>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        let valid = radius >= S::zero() && x.to_f64().is_finite() && y.to_f64().is_finite();
+        if !valid {
+            return Err(QuadTreeError::InvalidBounds);
+        }
+        let this = arc.read();
+        if !this.loose_bounds().intersects_circle(x, y, radius) {
+            return Ok(ControlFlow::Continue(()));
+        }
+        Ok(this.visit(&|bounds| bounds.intersects_circle(x, y, radius), &mut visit))
+    }
+
+    // read locks are held from the root down to the node being visited, the same order inserts lock in
+    fn visit<B, P, F>(&self, overlaps: &P, visit: &mut F) -> ControlFlow<B>
+    where
+        P: Fn(QuadTreeBoundsTs<S>) -> bool,
+        F: FnMut(&QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        for leaf in self.items.iter().chain(self.stuck.iter()) {
+            if overlaps(leaf.bounds) {
+                visit(leaf)?;
+            }
+        }
+        for branch in self.branches.iter().flatten() {
+            let branch = branch.read();
+            if overlaps(branch.loose_bounds()) {
+                branch.visit(overlaps, visit)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+
+    pub fn pairs(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        results: &mut Vec<QuadTreeLeafPairTs<T, S>>,
+    ) {
+        let _ = QuadTreeBranchTs::pairs_with(arc, |a, b| {
+            results.push((a.clone(), b.clone()));
+            ControlFlow::<()>::Continue(())
+        });
+    }
+
+    // every pair of intersecting leaves, each pair once, in a single walk of the tree
+    pub fn pairs_with<B, F>(arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>, mut visit: F) -> ControlFlow<B>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>, &QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        let this = arc.read();
+        this.visit_pairs(&[], &mut visit)
+    }
+
+    // ancestors holds the leaves above this node that can still reach it
+    fn visit_pairs<B, F>(&self, ancestors: &[&QuadTreeLeafTs<T, S>], visit: &mut F) -> ControlFlow<B>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>, &QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        let local: Vec<&QuadTreeLeafTs<T, S>> = self.items.iter().chain(self.stuck.iter()).collect();
+        for (i, leaf) in local.iter().enumerate() {
+            for other in ancestors.iter().chain(local[i + 1..].iter()) {
+                if leaf.bounds.intersects(other.bounds) {
+                    visit(other, leaf)?;
+                }
+            }
+        }
+
+        // siblings only overlap in loose mode, otherwise this returns straight away
+        let children: Vec<_> = self.branches.iter().flatten().map(|branch| branch.read()).collect();
+        for (i, child) in children.iter().enumerate() {
+            for other in children[i + 1..].iter() {
+                child.visit_cross(other, visit)?;
+            }
+        }
+
+        let mut below = Vec::with_capacity(ancestors.len() + local.len());
+        for child in children.iter() {
+            let reach = child.loose_bounds();
+            below.clear();
+            below.extend(
+                ancestors
+                    .iter()
+                    .chain(local.iter())
+                    .copied()
+                    .filter(|leaf| reach.intersects(leaf.bounds)),
+            );
+            child.visit_pairs(&below, visit)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    // pairs between this subtree and a sibling subtree
+    fn visit_cross<B, F>(&self, other: &QuadTreeBranchTs<T, S>, visit: &mut F) -> ControlFlow<B>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>, &QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        if !self.loose_bounds().intersects(other.loose_bounds()) {
+            return ControlFlow::Continue(());
+        }
+        let local: Vec<&QuadTreeLeafTs<T, S>> = self.items.iter().chain(self.stuck.iter()).collect();
+        other.visit_against(&local, visit)?;
+        for branch in self.branches.iter().flatten() {
+            branch.read().visit_cross(other, visit)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    // pairs between this subtree and leaves from outside it
+    fn visit_against<B, F>(&self, leaves: &[&QuadTreeLeafTs<T, S>], visit: &mut F) -> ControlFlow<B>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>, &QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        let reach = self.loose_bounds();
+        let leaves: Vec<&QuadTreeLeafTs<T, S>> =
+            leaves.iter().copied().filter(|leaf| reach.intersects(leaf.bounds)).collect();
+        if leaves.is_empty() {
+            return ControlFlow::Continue(());
+        }
+        for leaf in self.items.iter().chain(self.stuck.iter()) {
+            for other in leaves.iter() {
+                if leaf.bounds.intersects(other.bounds) {
+                    visit(other, leaf)?;
+                }
+            }
+        }
+        for branch in self.branches.iter().flatten() {
+            branch.read().visit_against(&leaves, visit)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    // depth first like leaves, but with every lock held so the copy is consistent
+    pub(crate) fn collect(arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>, results: &mut Vec<QuadTreeLeafTs<T, S>>) {
+        let _ = arc.read().visit(&|_| true, &mut |leaf| {
+            results.push(leaf.clone());
+            ControlFlow::<()>::Continue(())
+        });
+    }
+
+    pub fn leaves(arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>) -> QuadTreeLeavesTs<T, S> {
+        QuadTreeLeavesTs {
+            stack: vec![arc.clone()],
+            current: Vec::new().into_iter(),
+        }
+    }
+
+    pub fn nodes(arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>) -> QuadTreeNodesTs<T, S> {
+        QuadTreeNodesTs {
+            stack: vec![(arc.clone(), 0)],
+        }
+    }
+
+    fn info(&self, depth: usize) -> QuadTreeNodeInfo<QuadTreeBoundsTs<S>> {
+        QuadTreeNodeInfo {
+            depth,
+            bounds: self.bounds,
+            items: self.items.len(),
+            stuck: self.stuck.len(),
+            count: self.count,
+        }
+    }
+
+    pub fn snapshot(arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>) -> QuadTreeSnapshotTs<T, S> {
+        let this = arc.read();
+        QuadTreeSnapshotTs {
+            config: this.config,
+            root: this.snapshot_node(),
+        }
+    }
+
+    fn snapshot_node(&self) -> QuadTreeNodeSnapshotTs<T, S> {
+        let leaf_snapshot = |leaf: &QuadTreeLeafTs<T, S>| QuadTreeLeafSnapshotTs {
+            identity: leaf.identity,
+            bounds: leaf.bounds,
+            item: leaf.item.clone(),
+        };
+        QuadTreeNodeSnapshotTs {
+            bounds: self.bounds,
+            items: self.items.iter().map(leaf_snapshot).collect(),
+            stuck: self.stuck.iter().map(leaf_snapshot).collect(),
+            branches: self
+                .branches
+                .iter()
+                .map(|branch| branch.as_ref().map(|b| b.read().snapshot_node()))
+                .collect(),
+        }
+    }
+
+    pub fn restore(
+        snapshot: QuadTreeSnapshotTs<T, S>,
+    ) -> Result<Arc<RwLock<QuadTreeBranchTs<T, S>>>, QuadTreeError> {
+        QuadTreeBranchTs::restore_node(snapshot.root, None, None, None, snapshot.config)
+    }
+
+    // a snapshot comes from outside, every node has to sit in its parent and every leaf in its node
+    fn restore_node(
+        node: QuadTreeNodeSnapshotTs<T, S>,
+        parent: Option<Weak<RwLock<QuadTreeBranchTs<T, S>>>>,
+        outer: Option<QuadTreeBoundsTs<S>>,
+        tree: Option<u64>,
+        config: QuadTreeConfig<S>,
+    ) -> Result<Arc<RwLock<QuadTreeBranchTs<T, S>>>, QuadTreeError> {
+        let bounds = node.bounds;
+        if let Some(outer) = outer {
+            bounds.validate()?;
+            if !outer.contains(bounds) {
+                return Err(QuadTreeError::InvalidBounds);
+            }
+        }
+        let arc = QuadTreeBranchTs::with_config(
+            parent.is_none(),
+            bounds.x,
+            bounds.y,
+            bounds.w,
+            parent,
+            config,
+        );
+        let mut this = arc.write();
+        this.bounds = bounds;
+        this.tree = tree.unwrap_or(this.identity);
+        let tree = this.tree;
+        let loose = this.loose_bounds();
+
+        let restore_leaf = |leaf: QuadTreeLeafSnapshotTs<T, S>| {
+            leaf.bounds.validate()?;
+            if !loose.contains(leaf.bounds) {
+                return Err(QuadTreeError::InvalidBounds);
+            }
+            let leaf = QuadTreeLeafTs::restore(leaf.identity, leaf.item, leaf.bounds);
+            leaf.set_parent(Some(Arc::downgrade(&arc)));
+            Ok(leaf)
+        };
+        for leaf in node.items.into_iter().map(restore_leaf) {
+            QuadTreeBranchTs::push_leaf(&mut this.items, leaf?);
+        }
+        for leaf in node.stuck.into_iter().map(restore_leaf) {
+            QuadTreeBranchTs::push_leaf(&mut this.stuck, leaf?);
+        }
+        this.count = this.items.len() + this.stuck.len();
+
+        for (i, branch) in node.branches.into_iter().take(4).enumerate() {
+            if let Some(branch) = branch {
+                let parent = Some(Arc::downgrade(&arc));
+                let branch = QuadTreeBranchTs::restore_node(branch, parent, Some(loose), Some(tree), config)?;
+                this.count += branch.read().count;
+                this.branches[i] = Some(branch);
+            }
+        }
+        drop(this);
+        Ok(arc)
+    }
+
+    pub fn nearest(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        x: S,
+        y: S,
+        count: usize,
+        results: &mut Vec<(QuadTreeLeafTs<T, S>, f64)>,
+    ) -> Result<(), QuadTreeError> {
+        if !x.to_f64().is_finite() || !y.to_f64().is_finite() {
+            return Err(QuadTreeError::InvalidBounds);
+        }
+        let mut heap = BinaryHeap::new();
+        {
+            let this = arc.read();
+            heap.push(NearestEntry {
+                distance: this.loose_bounds().distance(x, y),
+                kind: NearestKind::Branch(arc.clone()),
+            });
+        }
+
+        let mut found = 0;
+        while found < count {
+            let Some(entry) = heap.pop() else {
+                break;
+            };
+            let arc = match entry.kind {
+                NearestKind::Leaf(leaf) => {
+                    results.push((leaf, entry.distance));
+                    found += 1;
+                    continue;
+                }
+                NearestKind::Branch(arc) => arc,
+            };
+
+            let tree = arc.read();
+            for i in 0..4 {
+                if tree.branches[i].is_none() {
+                    continue;
+                }
+                let branch_option = tree.branches[i].clone().unwrap();
+                let distance = branch_option.read().loose_bounds().distance(x, y);
+                heap.push(NearestEntry {
+                    distance,
+                    kind: NearestKind::Branch(branch_option),
+                });
+            }
+
+            for leaf in tree.items.iter().chain(tree.stuck.iter()) {
+                heap.push(NearestEntry {
+                    distance: leaf.bounds.distance(x, y),
+                    kind: NearestKind::Leaf(leaf.clone()),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // leaves the segment from (x0, y0) to (x1, y1) passes through, closest first, with how far along it each one is entered
+    pub fn query_segment(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        x0: S,
+        y0: S,
+        x1: S,
+        y1: S,
+        results: &mut Vec<(QuadTreeLeafTs<T, S>, f64)>,
+    ) -> Result<(), QuadTreeError> {
+        if [x0, y0, x1, y1].iter().any(|v| !v.to_f64().is_finite()) {
+            return Err(QuadTreeError::InvalidBounds);
+        }
+        let (dx, dy) = (x1.to_f64() - x0.to_f64(), y1.to_f64() - y0.to_f64());
+        let length = (dx * dx + dy * dy).sqrt();
+
+        let start = results.len();
+        let mut list = Vec::new();
+        if arc.read().loose_bounds().segment_entry(x0, y0, x1, y1).is_some() {
+            list.push(arc.clone());
+        }
+        while let Some(arc) = list.pop() {
+            let tree = arc.read();
+            // only the branches the segment actually crosses are opened
+            for branch_arc in tree.branches.iter().flatten() {
+                if branch_arc.read().loose_bounds().segment_entry(x0, y0, x1, y1).is_some() {
+                    list.push(branch_arc.clone());
+                }
+            }
+            for leaf in tree.items.iter().chain(tree.stuck.iter()) {
+                if let Some(entry) = leaf.bounds.segment_entry(x0, y0, x1, y1) {
+                    results.push((leaf.clone(), entry * length));
+                }
+            }
+        }
+        results[start..].sort_by(|a, b| a.1.total_cmp(&b.1));
+        Ok(())
+    }
+
+    // branches are only opened when the separating axis test can't rule them out, leaves get the exact test
+    pub fn query_polygon(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        polygon: &QuadTreePolygonTs<S>,
+        results: &mut Vec<QuadTreeLeafTs<T, S>>,
+    ) {
+        let mut list = Vec::new();
+        if !polygon.separated(arc.read().loose_bounds()) {
+            list.push(arc.clone());
+        }
+        while let Some(arc) = list.pop() {
+            let tree = arc.read();
+            for branch_arc in tree.branches.iter().flatten() {
+                if !polygon.separated(branch_arc.read().loose_bounds()) {
+                    list.push(branch_arc.clone());
+                }
+            }
+            for leaf in tree.items.iter().chain(tree.stuck.iter()) {
+                if polygon.overlaps(leaf.bounds) {
+                    results.push(leaf.clone());
+                }
+            }
+        }
+    }
+
+    fn adopt(arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>, index: usize) {
+        let this = arc.read();
+        if let Some(branch) = &this.branches[index] {
+            branch.write().parent = Some(Arc::downgrade(arc));
+        }
+    }
+
+    // twice the leaf's extent, or the min size, so the new root can split at least once
+    fn size_to(&mut self, bounds: QuadTreeBoundsTs<S>) -> Result<(), QuadTreeError> {
+        let extent = max(max(bounds.w, bounds.h), self.config.min_size);
+        let sized = match (
+            (bounds.x + bounds.w.half()).checked_sub(extent),
+            (bounds.y + bounds.h.half()).checked_sub(extent),
+            extent.checked_add(extent),
+        ) {
+            (Some(x), Some(y), Some(size)) => QuadTreeBoundsTs::new(x, y, size, size),
+            _ => return Err(QuadTreeError::OutOfRange),
+        };
+        if sized.checked_top().is_none() || sized.checked_right().is_none() {
+            return Err(QuadTreeError::OutOfRange);
+        }
+        self.bounds = sized;
+        Ok(())
+    }
+
+    // the bounds the root takes on its next grow
+    fn grown(bounds: QuadTreeBoundsTs<S>) -> Result<QuadTreeBoundsTs<S>, QuadTreeError> {
+        let size = bounds.w;
+        if size <= S::zero() {
+            // a negative root never gets any bigger, an empty one is sized by its first insert instead
+            return Err(QuadTreeError::InvalidBounds);
+        }
+        let half = size.half();
+        let grown = match (
+            bounds.x.checked_sub(half),
+            bounds.y.checked_sub(half),
+            bounds.w.checked_add(size),
+            bounds.h.checked_add(size),
+        ) {
+            (Some(x), Some(y), Some(w), Some(h)) => QuadTreeBoundsTs::new(x, y, w, h),
+            _ => return Err(QuadTreeError::OutOfRange),
+        };
+        if grown.checked_top().is_none() || grown.checked_right().is_none() {
+            return Err(QuadTreeError::OutOfRange);
+        }
+        Ok(grown)
+    }
+
+    // walks the root through the grows an insert would make, without touching the tree
+    fn reachable(mut root: QuadTreeBoundsTs<S>, bounds: QuadTreeBoundsTs<S>) -> Result<(), QuadTreeError> {
+        while !root.contains(bounds) {
+            root = QuadTreeBranchTs::<T, S>::grown(root)?;
+        }
+        Ok(())
+    }
+
+    fn grow(&mut self, zarc: Arc<RwLock<QuadTreeBranchTs<T, S>>>) -> Result<(), QuadTreeError> {
         //println!(" * * * GROWING * * * ");
+        let grown = QuadTreeBranchTs::<T, S>::grown(self.bounds)?;
         let size = self.bounds.w;
-        let half = size / 2;
+        let half = size.half();
 
-        // 0 1
-        // 3 2
-        if self.branches[0].is_some() {
-            let mut new_tree = QuadTreeBranchTs::new_unlocked(
-                false,
-                self.bounds.x - half,
-                self.bounds.y + half,
+        // each branch is wrapped by a node of the old size on its own side, and sits in its opposite quadrant
+        for i in 0..4 {
+            if self.branches[i].is_none() {
+                continue;
+            }
+            let (right, high_y) = self.orientation().halves(i);
+            let mut new_tree = self.new_child(
+                &zarc,
+                if right { self.bounds.x + half } else { self.bounds.x - half },
+                if high_y { self.bounds.y + half } else { self.bounds.y - half },
                 size,
-                Some(Arc::downgrade(&zarc)),
             );
-            new_tree.branches[2] = self.branches[0].clone();
-            self.branches[0] = Some(Arc::new(RwLock::new(new_tree)));
+            new_tree.count = self.branches[i].as_ref().unwrap().read().count;
+            new_tree.branches[(i + 2) % 4] = self.branches[i].clone();
+            let wrapper = Arc::new(RwLock::new(new_tree));
+            QuadTreeBranchTs::adopt(&wrapper, (i + 2) % 4);
+            self.branches[i] = Some(wrapper);
         }
-        
-        // 0 1
-        // 3 2
-        if !self.branches[1].is_none() {
-            let mut new_tree = QuadTreeBranchTs::new_unlocked(
-                false,
-                self.bounds.x + half,
-                self.bounds.y + half,
-                size,
-                Some(Arc::downgrade(&zarc)),
-            );
-            new_tree.branches[3] = self.branches[1].clone();
-            self.branches[1] = Some(Arc::new(RwLock::new(new_tree)));
+
+        self.bounds = grown;
+        Ok(())
+    }
+
+    pub fn from_iter<I>(
+        leaves: I,
+        config: QuadTreeConfig<S>,
+    ) -> Result<Arc<RwLock<QuadTreeBranchTs<T, S>>>, QuadTreeError>
+    where
+        I: IntoIterator<Item = QuadTreeLeafTs<T, S>>,
+    {
+        QuadTreeBranchTs::bulk(leaves.into_iter().collect(), config, false)
+    }
+
+    // the same as from_iter, with the top levels of the tree built on their own threads
+    pub fn from_iter_parallel<I>(
+        leaves: I,
+        config: QuadTreeConfig<S>,
+    ) -> Result<Arc<RwLock<QuadTreeBranchTs<T, S>>>, QuadTreeError>
+    where
+        I: IntoIterator<Item = QuadTreeLeafTs<T, S>>,
+    {
+        QuadTreeBranchTs::bulk(leaves.into_iter().collect(), config, true)
+    }
+
+    fn bulk(
+        leaves: Vec<QuadTreeLeafTs<T, S>>,
+        config: QuadTreeConfig<S>,
+        parallel: bool,
+    ) -> Result<Arc<RwLock<QuadTreeBranchTs<T, S>>>, QuadTreeError> {
+        // one square root around everything, so nothing ever has to grow
+        let mut enclosing: Option<(S, S, S, S)> = None;
+        for leaf in leaves.iter() {
+            let bounds = leaf.bounds;
+            bounds.validate()?;
+            let (Some(top), Some(right)) = (bounds.checked_top(), bounds.checked_right()) else {
+                return Err(QuadTreeError::OutOfRange);
+            };
+            enclosing = Some(match enclosing {
+                None => (bounds.left(), bounds.bottom(), right, top),
+                Some((l, b, r, t)) => (
+                    min(l, bounds.left()),
+                    min(b, bounds.bottom()),
+                    max(r, right),
+                    max(t, top),
+                ),
+            });
         }
 
-        // 0 1
-        // 3 2
-        if !self.branches[2].is_none() {
-            let mut new_tree = QuadTreeBranchTs::new_unlocked(
-                false,
-                self.bounds.x + half,
-                self.bounds.y - half,
-                size,
-                Some(Arc::downgrade(&zarc)),
-            );
-            new_tree.branches[0] = self.branches[2].clone();
-            self.branches[2] = Some(Arc::new(RwLock::new(new_tree)));
+        let zero = S::zero();
+        let (left, bottom, right, top) = enclosing.unwrap_or((zero, zero, zero, zero));
+        let size = match (right.checked_sub(left), top.checked_sub(bottom)) {
+            (Some(w), Some(h)) if max(w, h) > zero => max(w, h),
+            (Some(_), Some(_)) => S::from(1),
+            _ => return Err(QuadTreeError::OutOfRange),
+        };
+        if left.checked_add(size).is_none() || bottom.checked_add(size).is_none() {
+            return Err(QuadTreeError::OutOfRange);
         }
 
-        // 0 1
-        // 3 2
-        if !self.branches[3].is_none() {
-            let mut new_tree = QuadTreeBranchTs::new_unlocked(
-                false,
-                self.bounds.x - half,
-                self.bounds.y - half,
-                size,
-                Some(Arc::downgrade(&zarc)),
-            );
-            new_tree.branches[1] = self.branches[3].clone();
-            self.branches[3] = Some(Arc::new(RwLock::new(new_tree)));
+        let root = QuadTreeBranchTs::with_config(true, left, bottom, size, None, config);
+        QuadTreeBranchTs::build(&root, leaves, 0, parallel);
+        Ok(root)
+    }
+
+    // fills a fresh node with leaves that all fit it, they end up where one by one inserts would put them
+    fn build(
+        arc: &Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        leaves: Vec<QuadTreeLeafTs<T, S>>,
+        depth: usize,
+        parallel: bool,
+    ) {
+        let mut this = arc.write();
+        this.count = leaves.len();
+        let split = leaves.len() > this.config.capacity && this.config.can_split(this.bounds.w, depth);
+
+        let mut buckets: [Vec<QuadTreeLeafTs<T, S>>; 4] = Default::default();
+        for leaf in leaves {
+            if !split {
+                leaf.set_parent(Some(Arc::downgrade(arc)));
+                QuadTreeBranchTs::push_leaf(&mut this.items, leaf);
+                continue;
+            }
+            match this.target(leaf.bounds) {
+                Some(index) => buckets[index].push(leaf),
+                None => {
+                    leaf.set_parent(Some(Arc::downgrade(arc)));
+                    QuadTreeBranchTs::push_leaf(&mut this.stuck, leaf);
+                }
+            }
+        }
+
+        let mut work = Vec::new();
+        for (index, bucket) in buckets.into_iter().enumerate() {
+            if bucket.is_empty() {
+                continue;
+            }
+            let quadrant = Self::quadrant(this.bounds, index, this.orientation());
+            let branch = Arc::new(RwLock::new(this.new_child(arc, quadrant.x, quadrant.y, quadrant.w)));
+            this.branches[index] = Some(branch.clone());
+            work.push((branch, bucket));
+        }
+        drop(this);
+
+        // nothing else can see the tree yet, so each subtree only ever locks itself
+        if parallel && depth < BULK_PARALLEL_DEPTH {
+            thread::scope(|scope| {
+                for (branch, bucket) in work {
+                    scope.spawn(move || QuadTreeBranchTs::build(&branch, bucket, depth + 1, parallel));
+                }
+            });
+        } else {
+            for (branch, bucket) in work {
+                QuadTreeBranchTs::build(&branch, bucket, depth + 1, parallel);
+            }
         }
+    }
 
-        self.bounds.x -= half;
-        self.bounds.y -= half;
-        self.bounds.w += size;
-        self.bounds.h += size;
+    pub fn insert(
+        arc: Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        new_leaf: QuadTreeLeafTs<T, S>,
+    ) -> Result<(), QuadTreeError> {
+        let bounds = new_leaf.bounds;
+        bounds.validate()?;
+        if bounds.checked_top().is_none() || bounds.checked_right().is_none() {
+            return Err(QuadTreeError::OutOfRange);
+        }
+
+        let ancestors = QuadTreeBranchTs::ancestors(&arc);
+        for ancestor in ancestors.iter() {
+            ancestor.write().count += 1;
+        }
+        let result = QuadTreeBranchTs::insert_at(arc, new_leaf, ancestors.len());
+        if result.is_err() {
+            QuadTreeBranchTs::leave(&ancestors);
+        }
+        result
     }
 
-    pub fn insert(arc: Arc<RwLock<QuadTreeBranchTs<T>>>, mut new_leaf: QuadTreeLeafTs<T>) {
+    // only the root or a node cut off from its tree can fail, and only before anything has been changed
+    fn insert_at(
+        arc: Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+        new_leaf: QuadTreeLeafTs<T, S>,
+        depth: usize,
+    ) -> Result<(), QuadTreeError> {
         let mut this = arc.write();
+        if this.detached {
+            return Err(QuadTreeError::Detached);
+        }
 
         if this.root {
+            if this.count == 0 && this.bounds.w == S::zero() {
+                this.size_to(new_leaf.bounds)?;
+            }
             loop {
                 if this.bounds.contains(new_leaf.bounds) {
                     break;
                 }
 
-                QuadTreeBranchTs::grow(&mut *this, arc.clone());
+                QuadTreeBranchTs::grow(&mut *this, arc.clone())?;
             }
         }
         new_leaf.set_parent(Some(Arc::downgrade(&arc)));
-        this.items.push(new_leaf);
-        if this.items.len() < 2 {
-            return;
+        this.count += 1;
+        QuadTreeBranchTs::push_leaf(&mut this.items, new_leaf);
+        if this.items.len() <= this.config.capacity {
+            return Ok(());
         }
 
         while this.items.len() > 0 {
             let mut leaf = this.items.pop().unwrap();
 
-            let index = this.index(leaf.bounds);
-
-            if index < 0 || this.bounds.w < 16 {
+            let target = this.target(leaf.bounds);
+            if target.is_none() || !this.config.can_split(this.bounds.w, depth) {
                 leaf.set_parent(Some(Arc::downgrade(&arc)));
-                this.stuck.push(leaf);
+                QuadTreeBranchTs::push_leaf(&mut this.stuck, leaf);
                 continue;
             }
+            let index = target.unwrap();
 
-            let branch_option = this.branches.get(index as usize).unwrap();
+            let branch_option = this.branches.get(index).unwrap();
             let branch_arc = if branch_option.is_none() {
-                let size = this.bounds.w / 2;
-                let mut x = this.bounds.x;
-                let mut y = this.bounds.y;
-
-                // 0 1
-                // 3 2
-                if index == 0 {
-                    y += size;
-                } else if 1 == index {
-                    x += size;
-                    y += size;
-                } else if 2 == index {
-                    x += size;
-                }
-                
-                let new_branch =
-                    QuadTreeBranchTs::new(false, x, y, size, Some(Arc::downgrade(&arc)));
-                this.branches[index as usize] = Some(new_branch.clone());
+                let quadrant = Self::quadrant(this.bounds, index, this.orientation());
+                let new_branch = Arc::new(RwLock::new(this.new_child(&arc, quadrant.x, quadrant.y, quadrant.w)));
+                this.branches[index] = Some(new_branch.clone());
                 new_branch
             } else {
                 branch_option.clone().unwrap()
             };
 
-            QuadTreeBranchTs::insert(branch_arc, leaf);
+            QuadTreeBranchTs::insert_at(branch_arc, leaf, depth + 1)?;
         }
+        Ok(())
     }
 }
 
-fn drop_helper<T: Clone + Send + Sync>(
-    tree: &mut QuadTreeBranchTs<T>,
-    stack: &mut Vec<Arc<RwLock<QuadTreeBranchTs<T>>>>,
+// depth first, a node is only locked while its leaves are copied out, so writers can run between nodes
+pub struct QuadTreeLeavesTs<T, S = i64>
+where
+    T: Clone + Send + Sync + 'static,
+    S: QuadTreeScalar,
+{
+    stack: Vec<Arc<RwLock<QuadTreeBranchTs<T, S>>>>,
+    current: IntoIter<QuadTreeLeafTs<T, S>>,
+}
+
+impl<T, S> Iterator for QuadTreeLeavesTs<T, S>
+where
+    T: Clone + Send + Sync,
+    S: QuadTreeScalar,
+{
+    type Item = QuadTreeLeafTs<T, S>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(leaf) = self.current.next() {
+                return Some(leaf);
+            }
+            let arc = self.stack.pop()?;
+            let tree = arc.read();
+            // reversed so quadrant 0 comes off the stack first
+            self.stack.extend(tree.branches.iter().rev().flatten().cloned());
+            self.current = tree
+                .items
+                .iter()
+                .chain(tree.stuck.iter())
+                .cloned()
+                .collect::<Vec<_>>()
+                .into_iter();
+        }
+    }
+}
+
+// nodes still to visit, each with its depth
+type QuadTreeNodeStackTs<T, S> = Vec<(Arc<RwLock<QuadTreeBranchTs<T, S>>>, usize)>;
+
+// depth first, locking one node at a time like QuadTreeLeavesTs
+pub struct QuadTreeNodesTs<T, S = i64>
+where
+    T: Clone + Send + Sync + 'static,
+    S: QuadTreeScalar,
+{
+    stack: QuadTreeNodeStackTs<T, S>,
+}
+
+impl<T, S> Iterator for QuadTreeNodesTs<T, S>
+where
+    T: Clone + Send + Sync,
+    S: QuadTreeScalar,
+{
+    type Item = QuadTreeNodeInfo<QuadTreeBoundsTs<S>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (arc, depth) = self.stack.pop()?;
+        let tree = arc.read();
+        for branch in tree.branches.iter().rev().flatten() {
+            self.stack.push((branch.clone(), depth + 1));
+        }
+        Some(tree.info(depth))
+    }
+}
+
+enum NearestKind<T, S>
+where
+    T: Clone + Send + Sync + 'static,
+    S: QuadTreeScalar,
+{
+    Branch(Arc<RwLock<QuadTreeBranchTs<T, S>>>),
+    Leaf(QuadTreeLeafTs<T, S>),
+}
+
+// ordered so the BinaryHeap pops the closest entry first
+struct NearestEntry<T, S>
+where
+    T: Clone + Send + Sync + 'static,
+    S: QuadTreeScalar,
+{
+    distance: f64,
+    kind: NearestKind<T, S>,
+}
+
+impl<T, S> PartialEq for NearestEntry<T, S>
+where
+    T: Clone + Send + Sync,
+    S: QuadTreeScalar,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl<T, S> Eq for NearestEntry<T, S>
+where
+    T: Clone + Send + Sync,
+    S: QuadTreeScalar,
+{
+}
+
+impl<T, S> PartialOrd for NearestEntry<T, S>
+where
+    T: Clone + Send + Sync,
+    S: QuadTreeScalar,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T, S> Ord for NearestEntry<T, S>
+where
+    T: Clone + Send + Sync,
+    S: QuadTreeScalar,
+{
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        // leaves win ties so a leaf is reported before an equally distant branch is opened
+        other
+            .distance
+            .total_cmp(&self.distance)
+            .then_with(|| match (&self.kind, &other.kind) {
+                (NearestKind::Leaf(_), NearestKind::Branch(_)) => CmpOrdering::Greater,
+                (NearestKind::Branch(_), NearestKind::Leaf(_)) => CmpOrdering::Less,
+                _ => CmpOrdering::Equal,
+            })
+    }
+}
+
+fn drop_helper<T: Clone + Send + Sync, S: QuadTreeScalar>(
+    tree: &mut QuadTreeBranchTs<T, S>,
+    stack: &mut Vec<Arc<RwLock<QuadTreeBranchTs<T, S>>>>,
 ) {
     for i in 0..4 {
         if tree.branches[i].is_some() {
@@ -393,9 +1622,10 @@ fn drop_helper<T: Clone + Send + Sync>(
         }
     }
 }
-impl<T> Drop for QuadTreeBranchTs<T>
+impl<T, S> Drop for QuadTreeBranchTs<T, S>
 where
     T: Clone + Send + Sync,
+    S: QuadTreeScalar,
 {
     fn drop(&mut self) {
         let mut stack = Vec::new();
@@ -406,9 +1636,12 @@ where
             if tree_option.is_none() {
                 break;
             }
+            // branches still shared with someone else are left intact
             let arc = tree_option.unwrap();
-            let mut tree = arc.write();
-            drop_helper(&mut tree, &mut stack);
+            if let Some(lock) = Arc::into_inner(arc) {
+                let mut tree = lock.into_inner();
+                drop_helper(&mut tree, &mut stack);
+            }
         }
     }
 }
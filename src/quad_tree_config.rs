@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{quad_tree_orientation::QuadTreeOrientation, quad_tree_scalar::QuadTreeScalar};
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub struct QuadTreeConfig<S = i64> {
+    // a node splits once it holds more than this many items
+    pub capacity: usize,
+    // nodes narrower than this never split
+    pub min_size: S,
+    // nodes this deep never split, the root is depth 0
+    pub max_depth: usize,
+    // collapse single child roots left behind by removals
+    pub auto_shrink: bool,
+    // loose mode when above 1.0, every node reaches this many times its width so items sink by their centre
+    pub looseness: f64,
+    // which way y points, both trees number their quadrants and grow the same way for the same setting
+    // left unset each tree keeps its own, y up for the thread-safe tree and y down for QuadTree
+    pub orientation: Option<QuadTreeOrientation>,
+}
+
+impl<S> Default for QuadTreeConfig<S>
+where
+    S: QuadTreeScalar,
+{
+    fn default() -> Self {
+        Self {
+            capacity: 1,
+            min_size: S::from(16),
+            max_depth: usize::MAX,
+            auto_shrink: false,
+            looseness: 1.0,
+            orientation: None,
+        }
+    }
+}
+
+impl<S> QuadTreeConfig<S>
+where
+    S: QuadTreeScalar,
+{
+    pub fn new(capacity: usize, min_size: S, max_depth: usize) -> Self {
+        Self {
+            capacity,
+            min_size,
+            max_depth,
+            auto_shrink: false,
+            looseness: 1.0,
+            orientation: None,
+        }
+    }
+
+    pub(crate) fn can_split(&self, size: S, depth: usize) -> bool {
+        // an integer node of width one has nowhere left to split
+        size >= self.min_size && depth < self.max_depth && size.half() > S::zero()
+    }
+
+    pub(crate) fn is_loose(&self) -> bool {
+        self.looseness > 1.0
+    }
+
+    // how far a node of this width reaches past each of its edges
+    pub(crate) fn margin(&self, size: S) -> S {
+        if !self.is_loose() {
+            return S::zero();
+        }
+        S::from_f64(size.to_f64() * (self.looseness - 1.0) * 0.5)
+    }
+}
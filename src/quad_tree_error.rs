@@ -0,0 +1,29 @@
+use std::{error::Error, fmt::Display};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuadTreeError {
+    // the leaf has no parent, it was never inserted, already removed, or its tree was dropped
+    Detached,
+    // the leaf's parent no longer holds it
+    NotFound,
+    // a width or height that is not above zero, a nan coordinate, a negative radius or a root with a negative size
+    InvalidBounds,
+    // the bounds, or the root grown far enough to hold them, do not fit in the scalar type
+    OutOfRange,
+    // the receiving end of a streamed query hung up before the walk finished
+    Disconnected,
+}
+
+impl Display for QuadTreeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuadTreeError::Detached => f.write_str("leaf is not attached to a tree"),
+            QuadTreeError::NotFound => f.write_str("leaf was not found in its parent"),
+            QuadTreeError::InvalidBounds => f.write_str("bounds are empty, negative or not a number"),
+            QuadTreeError::OutOfRange => f.write_str("bounds are outside the representable range"),
+            QuadTreeError::Disconnected => f.write_str("query receiver hung up"),
+        }
+    }
+}
+
+impl Error for QuadTreeError {}
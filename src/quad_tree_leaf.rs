@@ -1,20 +1,60 @@
-use std::{cell::RefCell, rc::Weak};
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
 
-use crate::{quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds};
+use crate::{quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_scalar::QuadTreeScalar};
+
+// shared by every copy of a leaf, so a copy handed out by a query keeps up as the leaf moves
+struct QuadTreeLeafContainer<T, S>
+where
+    T: Clone,
+    S: QuadTreeScalar,
+{
+    parent: Weak<RefCell<QuadTree<T, S>>>,
+    // position in the parent's items or stuck list
+    slot: usize,
+}
 
 #[derive(Clone)]
-pub struct QuadTreeLeaf {
+pub struct QuadTreeLeaf<T, S = i64>
+where
+    T: Clone,
+    S: QuadTreeScalar,
+{
     pub identity: i64,
-    pub bounds: QuadTreeBounds,
-    pub parent: Weak<RefCell<QuadTree>>,
+    pub bounds: QuadTreeBounds<S>,
+    pub item: T,
+    container: Rc<RefCell<QuadTreeLeafContainer<T, S>>>,
 }
 
-impl QuadTreeLeaf {
-    pub fn new(identity: i64, bounds: QuadTreeBounds, parent: Weak<RefCell<QuadTree>>) -> Self {
+impl<T, S> QuadTreeLeaf<T, S>
+where
+    T: Clone,
+    S: QuadTreeScalar,
+{
+    pub fn new(identity: i64, item: T, bounds: QuadTreeBounds<S>, parent: Weak<RefCell<QuadTree<T, S>>>) -> Self {
         Self {
             bounds,
-            parent,
+            item,
             identity,
+            container: Rc::new(RefCell::new(QuadTreeLeafContainer { parent, slot: 0 })),
         }
     }
+
+    pub fn get_parent(&self) -> Option<Rc<RefCell<QuadTree<T, S>>>> {
+        self.container.borrow().parent.upgrade()
+    }
+
+    pub fn set_parent(&self, parent: Weak<RefCell<QuadTree<T, S>>>) {
+        self.container.borrow_mut().parent = parent;
+    }
+
+    pub(crate) fn get_slot(&self) -> usize {
+        self.container.borrow().slot
+    }
+
+    pub(crate) fn set_slot(&self, slot: usize) {
+        self.container.borrow_mut().slot = slot;
+    }
 }
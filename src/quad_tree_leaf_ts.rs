@@ -8,35 +8,46 @@ use std::{
 
 use parking_lot::{Mutex, RwLock};
 
-use crate::{quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_branch_ts::QuadTreeBranchTs};
+use crate::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_branch_ts::QuadTreeBranchTs,
+    quad_tree_error::QuadTreeError, quad_tree_scalar::QuadTreeScalar,
+};
 //-9223372036854775808
 static SEQUENCE: AtomicI64 = AtomicI64::new(-9007199254740991);
 //static SEQUENCE: AtomicU64 = AtomicU64::new(0);
 
 #[derive(Clone)]
-struct QuadTreeLeafContainer<T>
+struct QuadTreeLeafContainer<T, S>
 where
     T: Clone + Send + Sync + 'static,
+    S: QuadTreeScalar,
 {
-    parent: Option<Weak<RwLock<QuadTreeBranchTs<T>>>>,
+    parent: Option<Weak<RwLock<QuadTreeBranchTs<T, S>>>>,
+    // position in the parent's items or stuck list
+    slot: usize,
 }
 
 #[derive(Clone)]
-pub struct QuadTreeLeafTs<T>
+pub struct QuadTreeLeafTs<T, S = i64>
 where
     T: Clone + Send + Sync + 'static,
+    S: QuadTreeScalar,
 {
     pub(crate) identity: i64,
-    pub(crate) bounds: QuadTreeBoundsTs,
+    pub(crate) bounds: QuadTreeBoundsTs<S>,
     pub(crate) item: T,
-    container: Arc<Mutex<QuadTreeLeafContainer<T>>>,
+    container: Arc<Mutex<QuadTreeLeafContainer<T, S>>>,
 }
 
-impl<T> QuadTreeLeafTs<T>
+// two leaves whose bounds overlap, as reported by the broadphase
+pub type QuadTreeLeafPairTs<T, S = i64> = (QuadTreeLeafTs<T, S>, QuadTreeLeafTs<T, S>);
+
+impl<T, S> QuadTreeLeafTs<T, S>
 where
     T: Clone + Send + Sync + 'static,
+    S: QuadTreeScalar,
 {
-    pub fn new(item: T, bounds: QuadTreeBoundsTs) -> Self {
+    pub fn new(item: T, bounds: QuadTreeBoundsTs<S>) -> Self {
         Self {
             identity: SEQUENCE.fetch_add(1, Ordering::Relaxed),
             bounds,
@@ -44,15 +55,31 @@ where
             container: Arc::new(Mutex::new(QuadTreeLeafContainer {
                 
                 parent: None,
+                slot: 0,
             })),
         }
     }
 
-    pub fn remove(&mut self) -> bool {
+    pub(crate) fn restore(identity: i64, item: T, bounds: QuadTreeBoundsTs<S>) -> Self {
+        // keep new identities clear of the ones coming back from a snapshot
+        SEQUENCE.fetch_max(identity.saturating_add(1), Ordering::Relaxed);
+        Self {
+            identity,
+            bounds,
+            item,
+            container: Arc::new(Mutex::new(QuadTreeLeafContainer { parent: None, slot: 0 })),
+        }
+    }
+
+    pub fn remove(&mut self) -> Result<(), QuadTreeError> {
         QuadTreeBranchTs::remove(self)
     }
 
-    pub fn get_parent(&self) -> Option<Arc<RwLock<QuadTreeBranchTs<T>>>> {
+    pub fn update_bounds(&mut self, bounds: QuadTreeBoundsTs<S>) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::update_bounds(self, bounds)
+    }
+
+    pub fn get_parent(&self) -> Option<Arc<RwLock<QuadTreeBranchTs<T, S>>>> {
         let guard = self.container.lock();
         match &guard.parent {
             Some(parent) => parent.upgrade(),
@@ -60,11 +87,19 @@ where
         }
     }
 
-    pub fn set_parent(&self, parent: Option<Weak<parking_lot::lock_api::RwLock<parking_lot::RawRwLock, QuadTreeBranchTs<T>>>>) {
+    pub fn set_parent(&self, parent: Option<Weak<parking_lot::lock_api::RwLock<parking_lot::RawRwLock, QuadTreeBranchTs<T, S>>>>) {
         let mut guard = self.container.lock();
         guard.parent = parent;
     }
 
+    pub(crate) fn get_slot(&self) -> usize {
+        self.container.lock().slot
+    }
+
+    pub(crate) fn set_slot(&self, slot: usize) {
+        self.container.lock().slot = slot;
+    }
+
     pub fn get_identity(&self) -> i64 {
         self.identity
     }
@@ -72,15 +107,16 @@ where
     pub fn get_item(&self) -> T {
         self.item.clone()
     }
-    pub fn get_bounds(&self) -> QuadTreeBoundsTs {
+    pub fn get_bounds(&self) -> QuadTreeBoundsTs<S> {
         self.bounds
     }
 
 }
 
-impl<T> Display for QuadTreeLeafTs<T>
+impl<T, S> Display for QuadTreeLeafTs<T, S>
 where
     T: Clone + Send + Sync,
+    S: QuadTreeScalar,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         
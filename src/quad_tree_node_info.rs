@@ -0,0 +1,12 @@
+// a node as it was when a walk reached it, B is the bounds type of the tree it came from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuadTreeNodeInfo<B> {
+    // the root is depth 0
+    pub depth: usize,
+    pub bounds: B,
+    // leaves held by the node itself
+    pub items: usize,
+    pub stuck: usize,
+    // leaves in the node and everything below it
+    pub count: usize,
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+// which way y points, quadrants are numbered
+// 0 1
+// 3 2
+// from the top left either way
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum QuadTreeOrientation {
+    // larger y is further up, the top quadrants sit at the high y half
+    #[default]
+    YUp,
+    // screen space, the top quadrants sit at the low y half
+    YDown,
+}
+
+impl QuadTreeOrientation {
+    pub fn index(&self, right: bool, high_y: bool) -> usize {
+        let top = match self {
+            QuadTreeOrientation::YUp => high_y,
+            QuadTreeOrientation::YDown => !high_y,
+        };
+        match (top, right) {
+            (true, false) => 0,
+            (true, true) => 1,
+            (false, true) => 2,
+            (false, false) => 3,
+        }
+    }
+
+    // the inverse of index, whether the quadrant is on the right and on the high y half
+    pub fn halves(&self, index: usize) -> (bool, bool) {
+        let (top, right) = match index {
+            0 => (true, false),
+            1 => (true, true),
+            2 => (false, true),
+            _ => (false, false),
+        };
+        match self {
+            QuadTreeOrientation::YUp => (right, top),
+            QuadTreeOrientation::YDown => (right, !top),
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use std::f64::consts::TAU;
+
+use crate::{quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_error::QuadTreeError, quad_tree_scalar::QuadTreeScalar};
+
+// a closed polygon, the last point joins back to the first, either winding works
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuadTreePolygonTs<S = i64> {
+    points: Vec<(S, S)>,
+    convex: bool,
+}
+
+impl<S> QuadTreePolygonTs<S>
+where
+    S: QuadTreeScalar,
+{
+    pub fn new(points: Vec<(S, S)>) -> Result<Self, QuadTreeError> {
+        let finite = points.iter().all(|(x, y)| x.to_f64().is_finite() && y.to_f64().is_finite());
+        if points.len() < 3 || !finite {
+            return Err(QuadTreeError::InvalidBounds);
+        }
+        let convex = QuadTreePolygonTs::is_convex_ring(&points);
+        Ok(Self { points, convex })
+    }
+
+    pub fn points(&self) -> &[(S, S)] {
+        &self.points
+    }
+
+    pub fn is_convex(&self) -> bool {
+        self.convex
+    }
+
+    fn is_convex_ring(points: &[(S, S)]) -> bool {
+        // every turn goes the same way, straight runs don't count either way, doubling back does
+        // and the turns add up to a single lap, a star turns one way too but goes round twice
+        // a repeated point would hide the turn made at it
+        let n = points.len();
+        let ring: Vec<(S, S)> = (0..n).filter(|&i| points[i] != points[(i + 1) % n]).map(|i| points[i]).collect();
+        let (mut sign, mut turned) = (0.0f64, 0.0f64);
+        for (a, b, c) in QuadTreePolygonTs::corners(&ring) {
+            let cross = (b.0 - a.0) * (c.1 - b.1) - (b.1 - a.1) * (c.0 - b.0);
+            let dot = (b.0 - a.0) * (c.0 - b.0) + (b.1 - a.1) * (c.1 - b.1);
+            if cross == 0.0 {
+                if dot < 0.0 {
+                    return false;
+                }
+                continue;
+            }
+            if sign * cross < 0.0 {
+                return false;
+            }
+            sign = cross;
+            turned += cross.atan2(dot);
+        }
+        (turned.abs() - TAU).abs() < 1e-6
+    }
+
+    fn corners(points: &[(S, S)]) -> impl Iterator<Item = ((f64, f64), (f64, f64), (f64, f64))> + '_ {
+        let n = points.len();
+        let at = move |i: usize| (points[i % n].0.to_f64(), points[i % n].1.to_f64());
+        (0..n).map(move |i| (at(i), at(i + 1), at(i + 2)))
+    }
+
+    fn edges(&self) -> impl Iterator<Item = ((f64, f64), (f64, f64))> + '_ {
+        QuadTreePolygonTs::corners(&self.points).map(|(a, b, _)| (a, b))
+    }
+
+    // separating axis test on the box axes and every edge normal, the intervals have to overlap by more than a touch
+    // exact for convex polygons, a non convex one is only ever reported as overlapping too often, never too rarely
+    pub fn separated(&self, bounds: QuadTreeBoundsTs<S>) -> bool {
+        let (left, bottom) = (bounds.left().to_f64(), bounds.bottom().to_f64());
+        let (right, top) = (bounds.right().to_f64(), bounds.top().to_f64());
+        let corners = [(left, bottom), (right, bottom), (right, top), (left, top)];
+
+        let axes = [(1.0, 0.0), (0.0, 1.0)]
+            .into_iter()
+            .chain(self.edges().map(|(a, b)| (a.1 - b.1, b.0 - a.0)))
+            // a repeated point leaves an edge with no normal
+            .filter(|&(x, y): &(f64, f64)| x != 0.0 || y != 0.0);
+        for (ax, ay) in axes {
+            let project = |(x, y): (f64, f64)| x * ax + y * ay;
+            let (mut min_a, mut max_a) = (f64::INFINITY, f64::NEG_INFINITY);
+            for (x, y) in self.points.iter() {
+                let p = project((x.to_f64(), y.to_f64()));
+                min_a = min_a.min(p);
+                max_a = max_a.max(p);
+            }
+            let (mut min_b, mut max_b) = (f64::INFINITY, f64::NEG_INFINITY);
+            for corner in corners {
+                let p = project(corner);
+                min_b = min_b.min(p);
+                max_b = max_b.max(p);
+            }
+            if max_a <= min_b || max_b <= min_a {
+                return true;
+            }
+        }
+        false
+    }
+
+    // even odd rule, so holes made by a self crossing outline stay empty
+    pub fn contains_point(&self, x: S, y: S) -> bool {
+        let (x, y) = (x.to_f64(), y.to_f64());
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if (a.1 > y) != (b.1 > y) && x < a.0 + (y - a.1) * (b.0 - a.0) / (b.1 - a.1) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    pub fn overlaps(&self, bounds: QuadTreeBoundsTs<S>) -> bool {
+        if self.separated(bounds) {
+            return false;
+        }
+        if self.convex {
+            return true;
+        }
+        // an outline that cuts through the box, or a box that sits wholly inside the outline
+        let slabs = [
+            (bounds.left().to_f64(), bounds.right().to_f64()),
+            (bounds.bottom().to_f64(), bounds.top().to_f64()),
+        ];
+        let crossed = self.edges().any(|(a, b)| QuadTreePolygonTs::<S>::cuts(slabs, a, b));
+        crossed || self.contains_point(bounds.left(), bounds.bottom())
+    }
+
+    // slab test like QuadTreeBoundsTs::segment_entry, but running along an edge or through a corner doesn't count
+    fn cuts(slabs: [(f64, f64); 2], a: (f64, f64), b: (f64, f64)) -> bool {
+        let (mut enter, mut exit) = (0.0f64, 1.0f64);
+        for ((low, high), start, end) in [(slabs[0], a.0, b.0), (slabs[1], a.1, b.1)] {
+            let delta = end - start;
+            if delta == 0.0 {
+                if start <= low || start >= high {
+                    return false;
+                }
+                continue;
+            }
+            let (near, far) = ((low - start) / delta, (high - start) / delta);
+            enter = enter.max(near.min(far));
+            exit = exit.min(near.max(far));
+        }
+        enter < exit
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    fmt::{Debug, Display},
+    ops::{Add, Mul, Sub},
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+// coordinate type shared by the bounds and both trees
+pub trait QuadTreeScalar:
+    Copy
+    + Debug
+    + Display
+    + PartialEq
+    + PartialOrd
+    + Send
+    + Sync
+    + Serialize
+    + DeserializeOwned
+    + From<i8>
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + 'static
+{
+    // wide enough to square a difference of two coordinates
+    type Wide: Copy + PartialOrd + Add<Output = Self::Wide> + Sub<Output = Self::Wide> + Mul<Output = Self::Wide>;
+
+    fn zero() -> Self;
+    // integers round towards zero, floats keep the fraction
+    fn half(self) -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn saturating_add(self, other: Self) -> Self;
+    fn saturating_sub(self, other: Self) -> Self;
+    fn widen(self) -> Self::Wide;
+    fn to_f64(self) -> f64;
+    // integers round towards zero and saturate
+    fn from_f64(value: f64) -> Self;
+}
+
+macro_rules! int_scalar {
+    ($t:ty, $wide:ty) => {
+        impl QuadTreeScalar for $t {
+            type Wide = $wide;
+
+            fn zero() -> Self {
+                0
+            }
+            fn half(self) -> Self {
+                self / 2
+            }
+            fn checked_add(self, other: Self) -> Option<Self> {
+                <$t>::checked_add(self, other)
+            }
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                <$t>::checked_sub(self, other)
+            }
+            fn saturating_add(self, other: Self) -> Self {
+                <$t>::saturating_add(self, other)
+            }
+            fn saturating_sub(self, other: Self) -> Self {
+                <$t>::saturating_sub(self, other)
+            }
+            fn widen(self) -> Self::Wide {
+                self as $wide
+            }
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+        }
+    };
+}
+
+macro_rules! float_scalar {
+    ($t:ty) => {
+        impl QuadTreeScalar for $t {
+            type Wide = f64;
+
+            fn zero() -> Self {
+                0.0
+            }
+            fn half(self) -> Self {
+                self * 0.5
+            }
+            // anything that ends up infinite or nan is out of range, same as an integer overflow
+            fn checked_add(self, other: Self) -> Option<Self> {
+                finite(self + other)
+            }
+            fn checked_sub(self, other: Self) -> Option<Self> {
+                finite(self - other)
+            }
+            fn saturating_add(self, other: Self) -> Self {
+                self + other
+            }
+            fn saturating_sub(self, other: Self) -> Self {
+                self - other
+            }
+            fn widen(self) -> Self::Wide {
+                self as f64
+            }
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+        }
+    };
+}
+
+int_scalar!(i32, i128);
+int_scalar!(i64, i128);
+float_scalar!(f32);
+float_scalar!(f64);
+
+fn finite<S>(value: S) -> Option<S>
+where
+    S: QuadTreeScalar,
+{
+    if value.to_f64().is_finite() { Some(value) } else { None }
+}
+
+pub(crate) fn max<S>(a: S, b: S) -> S
+where
+    S: PartialOrd,
+{
+    if b > a { b } else { a }
+}
+
+pub(crate) fn min<S>(a: S, b: S) -> S
+where
+    S: PartialOrd,
+{
+    if b < a { b } else { a }
+}
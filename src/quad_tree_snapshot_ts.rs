@@ -0,0 +1,25 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_config::QuadTreeConfig};
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct QuadTreeSnapshotTs<T, S = i64> {
+    pub config: QuadTreeConfig<S>,
+    pub root: QuadTreeNodeSnapshotTs<T, S>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct QuadTreeNodeSnapshotTs<T, S = i64> {
+    pub bounds: QuadTreeBoundsTs<S>,
+    pub items: Vec<QuadTreeLeafSnapshotTs<T, S>>,
+    pub stuck: Vec<QuadTreeLeafSnapshotTs<T, S>>,
+    // always four entries, in the same quadrant order as the branch
+    pub branches: Vec<Option<QuadTreeNodeSnapshotTs<T, S>>>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct QuadTreeLeafSnapshotTs<T, S = i64> {
+    pub identity: i64,
+    pub bounds: QuadTreeBoundsTs<S>,
+    pub item: T,
+}
@@ -0,0 +1,326 @@
+use std::{
+    ops::ControlFlow,
+    sync::{
+        Arc,
+        mpsc::Sender,
+    },
+    vec::IntoIter,
+};
+
+use parking_lot::RwLock;
+use serde::{
+    Deserialize, Deserializer, Serialize, Serializer,
+    de::{DeserializeOwned, Error},
+};
+
+use crate::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_branch_ts::{QuadTreeBranchTs, QuadTreeLeavesTs, QuadTreeNodesTs},
+    quad_tree_config::QuadTreeConfig, quad_tree_error::QuadTreeError,
+    quad_tree_leaf_ts::{QuadTreeLeafPairTs, QuadTreeLeafTs}, quad_tree_polygon_ts::QuadTreePolygonTs,
+    quad_tree_scalar::QuadTreeScalar, quad_tree_snapshot_ts::QuadTreeSnapshotTs,
+};
+
+// owning handle around the root branch, clones share the same tree
+pub struct QuadTreeTs<T, S = i64>
+where
+    T: Clone + Send + Sync + 'static,
+    S: QuadTreeScalar,
+{
+    root: Arc<RwLock<QuadTreeBranchTs<T, S>>>,
+}
+
+impl<T, S> Clone for QuadTreeTs<T, S>
+where
+    T: Clone + Send + Sync,
+    S: QuadTreeScalar,
+{
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+        }
+    }
+}
+
+impl<T, S> QuadTreeTs<T, S>
+where
+    T: Clone + Send + Sync,
+    S: QuadTreeScalar,
+{
+    pub fn new(x: S, y: S, size: S) -> Self {
+        QuadTreeTs::with_config(x, y, size, QuadTreeConfig::default())
+    }
+
+    pub fn with_config(x: S, y: S, size: S, config: QuadTreeConfig<S>) -> Self {
+        Self {
+            root: QuadTreeBranchTs::with_config(true, x, y, size, None, config),
+        }
+    }
+
+    pub fn auto() -> Self {
+        QuadTreeTs::auto_with_config(QuadTreeConfig::default())
+    }
+
+    // no extent up front, the root is sized and centred by the first insert and grows from there
+    pub fn auto_with_config(config: QuadTreeConfig<S>) -> Self {
+        Self {
+            root: QuadTreeBranchTs::auto(config),
+        }
+    }
+
+    pub fn from_iter<I>(leaves: I, config: QuadTreeConfig<S>) -> Result<Self, QuadTreeError>
+    where
+        I: IntoIterator<Item = QuadTreeLeafTs<T, S>>,
+    {
+        Ok(Self {
+            root: QuadTreeBranchTs::from_iter(leaves, config)?,
+        })
+    }
+
+    pub fn from_iter_parallel<I>(leaves: I, config: QuadTreeConfig<S>) -> Result<Self, QuadTreeError>
+    where
+        I: IntoIterator<Item = QuadTreeLeafTs<T, S>>,
+    {
+        Ok(Self {
+            root: QuadTreeBranchTs::from_iter_parallel(leaves, config)?,
+        })
+    }
+
+    pub fn get_bounds(&self) -> QuadTreeBoundsTs<S> {
+        self.root.read().get_bounds()
+    }
+
+    pub fn insert(&self, leaf: QuadTreeLeafTs<T, S>) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::insert(self.root.clone(), leaf)
+    }
+
+    pub fn remove(&self, leaf: &QuadTreeLeafTs<T, S>) -> Result<(), QuadTreeError> {
+        self.owns(leaf)?;
+        QuadTreeBranchTs::remove(leaf)
+    }
+
+    pub fn update_bounds(
+        &self,
+        leaf: &mut QuadTreeLeafTs<T, S>,
+        bounds: QuadTreeBoundsTs<S>,
+    ) -> Result<(), QuadTreeError> {
+        self.owns(leaf)?;
+        QuadTreeBranchTs::update_bounds(leaf, bounds)
+    }
+
+    // a leaf that lives in some other tree is not found in this one
+    fn owns(&self, leaf: &QuadTreeLeafTs<T, S>) -> Result<(), QuadTreeError> {
+        let parent = leaf.get_parent().ok_or(QuadTreeError::Detached)?;
+        match QuadTreeBranchTs::same_tree(&parent, &self.root) {
+            true => Ok(()),
+            false => Err(QuadTreeError::NotFound),
+        }
+    }
+
+    pub fn query(
+        &self,
+        area: QuadTreeBoundsTs<S>,
+        results: &mut Vec<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::query(&self.root, area, results)
+    }
+
+    pub fn query_parallel(
+        &self,
+        area: QuadTreeBoundsTs<S>,
+        results: &mut Vec<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::query_parallel(&self.root, area, results)
+    }
+
+    pub fn query_with<B, F>(
+        &self,
+        area: QuadTreeBoundsTs<S>,
+        visit: F,
+    ) -> Result<ControlFlow<B>, QuadTreeError>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        QuadTreeBranchTs::query_with(&self.root, area, visit)
+    }
+
+    pub fn query_send(
+        &self,
+        area: QuadTreeBoundsTs<S>,
+        sender: &Sender<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::query_send(&self.root, area, sender)
+    }
+
+    pub fn query_send_batched(
+        &self,
+        area: QuadTreeBoundsTs<S>,
+        batch_size: usize,
+        sender: &Sender<Vec<QuadTreeLeafTs<T, S>>>,
+    ) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::query_send_batched(&self.root, area, batch_size, sender)
+    }
+
+    pub fn query_point(
+        &self,
+        x: S,
+        y: S,
+        results: &mut Vec<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::query_point(&self.root, x, y, results)
+    }
+
+    pub fn query_point_with<B, F>(
+        &self,
+        x: S,
+        y: S,
+        visit: F,
+    ) -> Result<ControlFlow<B>, QuadTreeError>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        QuadTreeBranchTs::query_point_with(&self.root, x, y, visit)
+    }
+
+    pub fn query_circle(
+        &self,
+        x: S,
+        y: S,
+        radius: S,
+        results: &mut Vec<QuadTreeLeafTs<T, S>>,
+    ) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::query_circle(&self.root, x, y, radius, results)
+    }
+
+    pub fn query_circle_with<B, F>(
+        &self,
+        x: S,
+        y: S,
+        radius: S,
+        visit: F,
+    ) -> Result<ControlFlow<B>, QuadTreeError>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        QuadTreeBranchTs::query_circle_with(&self.root, x, y, radius, visit)
+    }
+
+    pub fn pairs(&self, results: &mut Vec<QuadTreeLeafPairTs<T, S>>) {
+        QuadTreeBranchTs::pairs(&self.root, results);
+    }
+
+    pub fn pairs_with<B, F>(&self, visit: F) -> ControlFlow<B>
+    where
+        F: FnMut(&QuadTreeLeafTs<T, S>, &QuadTreeLeafTs<T, S>) -> ControlFlow<B>,
+    {
+        QuadTreeBranchTs::pairs_with(&self.root, visit)
+    }
+
+    pub fn nearest(
+        &self,
+        x: S,
+        y: S,
+        count: usize,
+        results: &mut Vec<(QuadTreeLeafTs<T, S>, f64)>,
+    ) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::nearest(&self.root, x, y, count, results)
+    }
+
+    pub fn query_segment(
+        &self,
+        x0: S,
+        y0: S,
+        x1: S,
+        y1: S,
+        results: &mut Vec<(QuadTreeLeafTs<T, S>, f64)>,
+    ) -> Result<(), QuadTreeError> {
+        QuadTreeBranchTs::query_segment(&self.root, x0, y0, x1, y1, results)
+    }
+
+    pub fn query_polygon(&self, polygon: &QuadTreePolygonTs<S>, results: &mut Vec<QuadTreeLeafTs<T, S>>) {
+        QuadTreeBranchTs::query_polygon(&self.root, polygon, results);
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.read().is_empty()
+    }
+
+    pub fn branch_lens(&self) -> [usize; 4] {
+        self.root.read().branch_lens()
+    }
+
+    pub fn shrink(&self) -> bool {
+        QuadTreeBranchTs::shrink(&self.root)
+    }
+
+    pub fn clear(&self) {
+        self.root.write().clear();
+    }
+
+    pub fn snapshot(&self) -> QuadTreeSnapshotTs<T, S> {
+        QuadTreeBranchTs::snapshot(&self.root)
+    }
+
+    pub fn restore(snapshot: QuadTreeSnapshotTs<T, S>) -> Result<Self, QuadTreeError> {
+        Ok(Self {
+            root: QuadTreeBranchTs::restore(snapshot)?,
+        })
+    }
+
+    pub fn leaves(&self) -> QuadTreeLeavesTs<T, S> {
+        QuadTreeBranchTs::leaves(&self.root)
+    }
+
+    pub fn nodes(&self) -> QuadTreeNodesTs<T, S> {
+        QuadTreeBranchTs::nodes(&self.root)
+    }
+
+    pub fn iter(&self) -> IntoIter<QuadTreeLeafTs<T, S>> {
+        let mut results = Vec::new();
+        QuadTreeBranchTs::collect(&self.root, &mut results);
+        results.into_iter()
+    }
+}
+
+impl<T, S> IntoIterator for &QuadTreeTs<T, S>
+where
+    T: Clone + Send + Sync,
+    S: QuadTreeScalar,
+{
+    type Item = QuadTreeLeafTs<T, S>;
+    type IntoIter = IntoIter<QuadTreeLeafTs<T, S>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, S> Serialize for QuadTreeTs<T, S>
+where
+    T: Clone + Send + Sync + Serialize,
+    S: QuadTreeScalar,
+{
+    fn serialize<R>(&self, serializer: R) -> Result<R::Ok, R::Error>
+    where
+        R: Serializer,
+    {
+        self.snapshot().serialize(serializer)
+    }
+}
+
+impl<'de, T, S> Deserialize<'de> for QuadTreeTs<T, S>
+where
+    T: Clone + Send + Sync + DeserializeOwned,
+    S: QuadTreeScalar,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let snapshot = QuadTreeSnapshotTs::deserialize(deserializer)?;
+        QuadTreeTs::restore(snapshot).map_err(D::Error::custom)
+    }
+}
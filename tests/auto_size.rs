@@ -0,0 +1,76 @@
+use std::{cell::RefCell, rc::Rc};
+
+use quad_tree::{
+    quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_config::QuadTreeConfig, quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_ts::QuadTreeTs,
+};
+
+// a cluster well away from the origin, which a fixed root has to grow a long way to reach
+fn boxes() -> Vec<(i64, i64, i64, i64)> {
+    (0..400i64).map(|i| (700 + (i * 37) % 900, -500 + (i * 53) % 700, 1 + i % 5, 1 + i % 3)).collect()
+}
+
+#[test]
+fn the_first_leaf_centres_an_auto_sized_root() {
+    let tree: QuadTreeTs<usize> = QuadTreeTs::auto();
+    let bounds = QuadTreeBoundsTs::new(1234, -567, 9, 4);
+    tree.insert(QuadTreeLeafTs::new(0, bounds)).unwrap();
+
+    let root = tree.get_bounds();
+    assert!(root.w > 0 && root.contains(bounds));
+    assert!((root.x + root.w / 2 - (bounds.x + bounds.w / 2)).abs() <= 1);
+    assert!((root.y + root.h / 2 - (bounds.y + bounds.h / 2)).abs() <= 1);
+}
+
+#[test]
+fn auto_sized_trees_answer_like_fixed_ones() {
+    let config = QuadTreeConfig::new(2, 2, 32);
+    let auto: QuadTreeTs<usize> = QuadTreeTs::auto_with_config(config);
+    let fixed: QuadTreeTs<usize> = QuadTreeTs::with_config(0, 0, 1, config);
+    let single_auto = Rc::new(RefCell::new(QuadTree::auto(config)));
+    let single_fixed = Rc::new(RefCell::new(QuadTree::with_config(true, 0, 0, 1, None, config)));
+
+    let boxes = boxes();
+    for (i, &(x, y, w, h)) in boxes.iter().enumerate() {
+        auto.insert(QuadTreeLeafTs::new(i, QuadTreeBoundsTs::new(x, y, w, h))).unwrap();
+        fixed.insert(QuadTreeLeafTs::new(i, QuadTreeBoundsTs::new(x, y, w, h))).unwrap();
+        QuadTree::insert(single_auto.clone(), i as i64, i, QuadTreeBounds::new(x, y, w, h), 0).unwrap();
+        QuadTree::insert(single_fixed.clone(), i as i64, i, QuadTreeBounds::new(x, y, w, h), 0).unwrap();
+    }
+
+    for (x, y, w, h) in [(700, -500, 300, 300), (900, -100, 500, 400), (0, -1000, 5000, 5000)] {
+        let area = QuadTreeBoundsTs::new(x, y, w, h);
+        let expected: Vec<usize> = (0..boxes.len())
+            .filter(|&i| area.intersects(QuadTreeBoundsTs::new(boxes[i].0, boxes[i].1, boxes[i].2, boxes[i].3)))
+            .collect();
+        assert!(!expected.is_empty());
+
+        for tree in [&auto, &fixed] {
+            let mut results = Vec::new();
+            tree.query(area, &mut results).unwrap();
+            let mut items: Vec<usize> = results.iter().map(|l| l.get_item()).collect();
+            items.sort();
+            assert_eq!(items, expected);
+        }
+        for tree in [&single_auto, &single_fixed] {
+            let mut results = Vec::new();
+            QuadTree::query(tree.clone(), QuadTreeBounds::new(x, y, w, h), &mut results).unwrap();
+            let mut items: Vec<usize> = results.iter().map(|l| l.item).collect();
+            items.sort();
+            assert_eq!(items, expected);
+        }
+    }
+}
+
+#[test]
+fn auto_sized_float_trees_take_fractional_boxes() {
+    let tree: QuadTreeTs<u8, f64> = QuadTreeTs::auto();
+    tree.insert(QuadTreeLeafTs::new(1, QuadTreeBoundsTs::new(-0.25, 0.5, 0.5, 0.25))).unwrap();
+    tree.insert(QuadTreeLeafTs::new(2, QuadTreeBoundsTs::new(900.0, 0.5, 0.5, 0.25))).unwrap();
+    assert_eq!(tree.len(), 2);
+
+    let mut results = Vec::new();
+    tree.query(QuadTreeBoundsTs::new(-1.0, 0.0, 1.0, 1.0), &mut results).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].get_item(), 1);
+}
@@ -0,0 +1,75 @@
+use quad_tree::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_config::QuadTreeConfig, quad_tree_error::QuadTreeError,
+    quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_ts::QuadTreeTs,
+};
+
+const COUNT: usize = 20000;
+
+fn boxes() -> Vec<QuadTreeBoundsTs> {
+    (0..COUNT as i64)
+        .map(|i| QuadTreeBoundsTs::new((i * 7919) % 5000 - 1700, (i * 104729) % 4000 - 900, 1 + i % 9, 1 + i % 5))
+        .collect()
+}
+
+fn check(tree: &QuadTreeTs<usize>, boxes: &[QuadTreeBoundsTs]) {
+    for q in 0..50i64 {
+        let area = QuadTreeBoundsTs::new((q * 97) % 5000 - 1700, (q * 31) % 4000 - 900, 60, 45);
+        let mut results = Vec::new();
+        tree.query(area, &mut results).unwrap();
+        let mut items: Vec<usize> = results.iter().map(|l| l.get_item()).collect();
+        items.sort();
+        let expected: Vec<usize> = (0..boxes.len()).filter(|&i| area.intersects(boxes[i])).collect();
+        assert_eq!(items, expected);
+    }
+}
+
+#[test]
+fn bulk_loaded_trees_answer_like_inserted_ones() {
+    let boxes = boxes();
+    for looseness in [1.0, 2.0] {
+        let mut config = QuadTreeConfig::new(4, 2, 32);
+        config.looseness = looseness;
+        let leaves: Vec<_> = boxes.iter().enumerate().map(|(i, b)| QuadTreeLeafTs::new(i, *b)).collect();
+
+        for parallel in [false, true] {
+            let tree = match parallel {
+                false => QuadTreeTs::from_iter(leaves.clone(), config),
+                true => QuadTreeTs::from_iter_parallel(leaves.clone(), config),
+            }
+            .unwrap();
+            assert_eq!(tree.len(), COUNT);
+            assert_eq!(tree.iter().count(), COUNT);
+            check(&tree, &boxes);
+
+            // the handles passed in are live, so they can be moved and removed straight away
+            for leaf in leaves.iter().step_by(7) {
+                tree.remove(leaf).unwrap();
+            }
+            assert_eq!(tree.len(), COUNT - leaves.iter().step_by(7).count());
+            let mut moved = leaves[1].clone();
+            tree.update_bounds(&mut moved, QuadTreeBoundsTs::new(9000, 9000, 2, 2)).unwrap();
+            tree.update_bounds(&mut moved, boxes[1]).unwrap();
+            for leaf in leaves.iter().step_by(7) {
+                tree.insert(leaf.clone()).unwrap();
+            }
+            check(&tree, &boxes);
+        }
+    }
+}
+
+#[test]
+fn bulk_loading_nothing_gives_a_usable_tree() {
+    let tree: QuadTreeTs<usize> = QuadTreeTs::from_iter(Vec::new(), QuadTreeConfig::default()).unwrap();
+    assert!(tree.is_empty());
+    tree.insert(QuadTreeLeafTs::new(1, QuadTreeBoundsTs::new(500, 500, 3, 3))).unwrap();
+    assert_eq!(tree.len(), 1);
+}
+
+#[test]
+fn bulk_loading_bad_bounds_fails() {
+    let leaves = vec![
+        QuadTreeLeafTs::new(0usize, QuadTreeBoundsTs::new(0, 0, 2, 2)),
+        QuadTreeLeafTs::new(1usize, QuadTreeBoundsTs::new(5, 5, -2, 2)),
+    ];
+    assert_eq!(QuadTreeTs::from_iter(leaves, QuadTreeConfig::default()).err(), Some(QuadTreeError::InvalidBounds));
+}
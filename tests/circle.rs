@@ -0,0 +1,60 @@
+use std::{cell::RefCell, rc::Rc};
+
+use quad_tree::{
+    quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_ts::QuadTreeTs,
+};
+
+#[test]
+fn circles_only_reach_boxes_within_the_radius() {
+    let ts = QuadTreeTs::new(0, 0, 64);
+    let single = Rc::new(RefCell::new(QuadTree::new(true, 0, 0, 64, None)));
+    // the corner of the first box is 5 from the centre, the second one is 6 away along x
+    ts.insert(QuadTreeLeafTs::new(0, QuadTreeBoundsTs::new(23, 24, 2, 2))).unwrap();
+    ts.insert(QuadTreeLeafTs::new(1, QuadTreeBoundsTs::new(26, 19, 2, 2))).unwrap();
+    QuadTree::insert(single.clone(), 0, 0, QuadTreeBounds::new(23, 24, 2, 2), 0).unwrap();
+    QuadTree::insert(single.clone(), 1, 1, QuadTreeBounds::new(26, 19, 2, 2), 0).unwrap();
+
+    for (radius, expected) in [(5, 0), (6, 1), (7, 2)] {
+        let mut results = Vec::new();
+        ts.query_circle(20, 20, radius, &mut results).unwrap();
+        assert_eq!(results.len(), expected, "radius {radius}");
+
+        let mut results = Vec::new();
+        QuadTree::query_circle(single.clone(), 20, 20, radius, &mut results).unwrap();
+        assert_eq!(results.len(), expected, "radius {radius}");
+    }
+}
+
+#[test]
+fn circles_far_across_the_range_do_not_overflow() {
+    let x = i64::MIN + 10;
+    let ts = QuadTreeTs::new(x, 0, 64);
+    let single = Rc::new(RefCell::new(QuadTree::new(true, x, 0, 64, None)));
+    ts.insert(QuadTreeLeafTs::new(0, QuadTreeBoundsTs::new(x, 0, 1, 1))).unwrap();
+    QuadTree::insert(single.clone(), 0, 0, QuadTreeBounds::new(x, 0, 1, 1), 0).unwrap();
+
+    let mut results = Vec::new();
+    ts.query_circle(i64::MAX, 0, 1, &mut results).unwrap();
+    ts.query_circle(i64::MAX, i64::MAX, i64::MAX, &mut results).unwrap();
+    assert!(results.is_empty());
+
+    let mut results = Vec::new();
+    QuadTree::query_circle(single.clone(), i64::MAX, 0, 1, &mut results).unwrap();
+    QuadTree::query_circle(single.clone(), i64::MAX, i64::MAX, i64::MAX, &mut results).unwrap();
+    assert!(results.is_empty());
+}
+
+#[test]
+fn narrow_scalars_square_their_distances_without_overflow() {
+    let ts = QuadTreeTs::<usize, i32>::new(-2_000_000_000, 0, 64);
+    ts.insert(QuadTreeLeafTs::new(0, QuadTreeBoundsTs::new(-2_000_000_000, 0, 1, 1))).unwrap();
+
+    let mut results = Vec::new();
+    ts.query_circle(2_000_000_000, 0, 1, &mut results).unwrap();
+    ts.query_circle(i32::MAX, i32::MAX, i32::MAX, &mut results).unwrap();
+    assert!(results.is_empty());
+
+    ts.query_circle(-1_999_999_990, 0, 10, &mut results).unwrap();
+    assert_eq!(results.len(), 1);
+}
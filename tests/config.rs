@@ -0,0 +1,33 @@
+use std::{cell::RefCell, rc::Rc};
+
+use quad_tree::{
+    quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_config::QuadTreeConfig, quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_ts::QuadTreeTs,
+};
+
+#[test]
+fn unbounded_capacity_never_splits() {
+    let config = QuadTreeConfig::new(usize::MAX, 1, usize::MAX);
+    let ts = QuadTreeTs::with_config(0, 0, 64, config);
+    let single = Rc::new(RefCell::new(QuadTree::with_config(true, 0, 0, 64, None, config)));
+    for i in 0..100i64 {
+        ts.insert(QuadTreeLeafTs::new(i, QuadTreeBoundsTs::new(i % 60, i / 2, 2, 2))).unwrap();
+        QuadTree::insert(single.clone(), i, i, QuadTreeBounds::new(i % 60, i / 2, 2, 2), 0).unwrap();
+    }
+
+    assert_eq!(ts.len(), 100);
+    assert_eq!(ts.branch_lens(), [0; 4]);
+    assert_eq!(single.borrow().branch_lens(), [0; 4]);
+}
+
+#[test]
+fn max_depth_caps_the_tree() {
+    let ts = QuadTreeTs::with_config(0, 0, 1024, QuadTreeConfig::new(1, 1, 2));
+    for i in 0..300i64 {
+        let bounds = QuadTreeBoundsTs::new((i * 7) % 1000, (i * 11) % 1000, 1, 1);
+        ts.insert(QuadTreeLeafTs::new(i, bounds)).unwrap();
+    }
+
+    assert_eq!(ts.len(), 300);
+    assert!(ts.nodes().all(|node| node.depth <= 2));
+}
@@ -0,0 +1,60 @@
+use std::{cell::RefCell, rc::Rc};
+
+use quad_tree::{
+    quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_config::QuadTreeConfig, quad_tree_leaf_ts::QuadTreeLeafTs,
+    quad_tree_orientation::QuadTreeOrientation, quad_tree_ts::QuadTreeTs,
+};
+
+const COUNT: i64 = 300;
+
+struct Trees {
+    ts: QuadTreeTs<usize>,
+    single: Rc<RefCell<QuadTree<usize>>>,
+}
+
+// the same boxes in both trees, spread far enough to grow the roots a few times
+fn build() -> Trees {
+    // both trees number their quadrants the same way, so their nodes come out in the same order
+    let mut config = QuadTreeConfig::new(2, 4, 16);
+    config.orientation = Some(QuadTreeOrientation::YUp);
+    let ts = QuadTreeTs::with_config(0, 0, 64, config);
+    let single = Rc::new(RefCell::new(QuadTree::with_config(true, 0, 0, 64, None, config)));
+    for i in 0..COUNT {
+        let (x, y) = ((i * 37) % 500 - 200, (i * 53) % 400 - 150);
+        ts.insert(QuadTreeLeafTs::new(i as usize, QuadTreeBoundsTs::new(x, y, 3, 2))).unwrap();
+        QuadTree::insert(single.clone(), i, i as usize, QuadTreeBounds::new(x, y, 3, 2), 0).unwrap();
+    }
+    Trees { ts, single }
+}
+
+#[test]
+fn leaves_visit_every_leaf_once() {
+    let trees = build();
+
+    let mut leaves: Vec<usize> = trees.ts.leaves().map(|l| l.get_item()).collect();
+    let collected: Vec<usize> = trees.ts.iter().map(|l| l.get_item()).collect();
+    assert_eq!(leaves, collected);
+    leaves.sort();
+    assert_eq!(leaves, (0..COUNT as usize).collect::<Vec<_>>());
+
+    let mut single: Vec<usize> = QuadTree::leaves(trees.single.clone()).map(|l| l.item).collect();
+    single.sort();
+    assert_eq!(single, leaves);
+}
+
+#[test]
+fn nodes_report_depth_and_counts() {
+    let trees = build();
+
+    let nodes: Vec<_> = trees.ts.nodes().collect();
+    assert_eq!(nodes[0].depth, 0);
+    assert_eq!(nodes[0].count, COUNT as usize);
+    assert_eq!(nodes.iter().map(|n| n.items + n.stuck).sum::<usize>(), COUNT as usize);
+    assert!(nodes.iter().any(|n| n.depth > 1));
+
+    let single: Vec<_> = QuadTree::nodes(trees.single.clone()).collect();
+    let single: Vec<(usize, usize)> = single.iter().map(|n| (n.depth, n.count)).collect();
+    let nodes: Vec<(usize, usize)> = nodes.iter().map(|n| (n.depth, n.count)).collect();
+    assert_eq!(single, nodes);
+}
@@ -0,0 +1,116 @@
+use std::{cell::RefCell, ops::ControlFlow, rc::Rc};
+
+use quad_tree::{
+    quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_config::QuadTreeConfig, quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_ts::QuadTreeTs,
+};
+
+const COUNT: usize = 2000;
+
+fn config(looseness: f64) -> QuadTreeConfig {
+    let mut config = QuadTreeConfig::new(4, 8, 32);
+    config.looseness = looseness;
+    config
+}
+
+// half of them hug a centre line, which a tight tree can only keep in its root
+fn boxes() -> Vec<QuadTreeBoundsTs> {
+    (0..COUNT as i64)
+        .map(|i| {
+            let x = if i % 2 == 0 { 505 + i % 11 } else { (i * 37) % 1000 };
+            let y = if i % 2 == 1 { 505 + i % 13 } else { (i * 53) % 1000 };
+            QuadTreeBoundsTs::new(x, y, 4 + i % 5, 4)
+        })
+        .collect()
+}
+
+fn held_by_root(tree: &QuadTreeTs<usize>) -> usize {
+    tree.len() - tree.branch_lens().iter().sum::<usize>()
+}
+
+// every query has to agree with a brute force pass over the boxes still in the tree
+fn check(tree: &QuadTreeTs<usize>, boxes: &[QuadTreeBoundsTs], alive: impl Fn(usize) -> bool) {
+    for q in 0..80i64 {
+        let area = QuadTreeBoundsTs::new((q * 97) % 1000 - 20, (q * 31) % 1000 - 20, 40 + q, 30);
+        let mut results = Vec::new();
+        tree.query(area, &mut results).unwrap();
+        let mut items: Vec<usize> = results.iter().map(|l| l.get_item()).collect();
+        items.sort();
+        let expected: Vec<usize> = (0..boxes.len()).filter(|&i| alive(i) && area.intersects(boxes[i])).collect();
+        assert_eq!(items, expected);
+
+        let (x, y) = ((q * 13) % 1000, 510);
+        let mut hits = 0;
+        let flow = tree
+            .query_circle_with(x, y, 25, |_| {
+                hits += 1;
+                ControlFlow::<()>::Continue(())
+            })
+            .unwrap();
+        assert!(flow.is_continue());
+        let expected = (0..boxes.len()).filter(|&i| alive(i) && boxes[i].intersects_circle(x, y, 25)).count();
+        assert_eq!(hits, expected);
+    }
+
+    let mut nearest = Vec::new();
+    tree.nearest(300, 700, 10, &mut nearest).unwrap();
+    let mut distances: Vec<f64> = (0..boxes.len()).filter(|&i| alive(i)).map(|i| boxes[i].distance(300, 700)).collect();
+    distances.sort_by(|a, b| a.total_cmp(b));
+    let found: Vec<f64> = nearest.iter().map(|(_, d)| *d).collect();
+    assert_eq!(found, distances[..10]);
+}
+
+#[test]
+fn loose_nodes_sink_what_a_tight_tree_keeps_in_the_root() {
+    let tight = QuadTreeTs::with_config(0, 0, 1024, config(1.0));
+    let loose = QuadTreeTs::with_config(0, 0, 1024, config(2.0));
+    for (i, b) in boxes().into_iter().enumerate() {
+        tight.insert(QuadTreeLeafTs::new(i, b)).unwrap();
+        loose.insert(QuadTreeLeafTs::new(i, b)).unwrap();
+    }
+
+    assert!(held_by_root(&tight) > 100, "{}", held_by_root(&tight));
+    assert!(held_by_root(&loose) < 5, "{}", held_by_root(&loose));
+}
+
+#[test]
+fn loose_queries_match_brute_force_through_moves_and_removals() {
+    let tree = QuadTreeTs::with_config(0, 0, 1024, config(2.0));
+    let mut boxes = boxes();
+    let mut leaves = Vec::new();
+    for (i, b) in boxes.iter().enumerate() {
+        let leaf = QuadTreeLeafTs::new(i, *b);
+        tree.insert(leaf.clone()).unwrap();
+        leaves.push(leaf);
+    }
+    check(&tree, &boxes, |_| true);
+
+    for i in (0..COUNT).step_by(4) {
+        let b = QuadTreeBoundsTs::new(((i * 7) % 1200) as i64 - 100, ((i * 3) % 900) as i64, 3, 9);
+        boxes[i] = b;
+        tree.update_bounds(&mut leaves[i], b).unwrap();
+    }
+    for leaf in leaves.iter().skip(1).step_by(4) {
+        tree.remove(leaf).unwrap();
+    }
+    check(&tree, &boxes, |i| i % 4 != 1);
+}
+
+#[test]
+fn single_threaded_loose_queries_match_brute_force() {
+    let root = Rc::new(RefCell::new(QuadTree::<usize>::with_config(true, 0, 0, 1024, None, config(2.0))));
+    let boxes: Vec<QuadTreeBounds> = boxes().iter().map(|b| QuadTreeBounds::new(b.x, b.y, b.w, b.h)).collect();
+    for (i, b) in boxes.iter().enumerate() {
+        QuadTree::insert(root.clone(), i as i64, i, *b, 0).unwrap();
+    }
+
+    for q in 0..40i64 {
+        let area = QuadTreeBounds::new((q * 97) % 1000 - 20, (q * 31) % 1000 - 20, 40 + q, 30);
+        let mut results = Vec::new();
+        QuadTree::query(root.clone(), area, &mut results).unwrap();
+        let mut items: Vec<usize> = results.iter().map(|l| l.item).collect();
+        items.sort();
+        let expected: Vec<usize> = (0..COUNT).filter(|&i| area.intersects(&boxes[i])).collect();
+        assert_eq!(items, expected);
+    }
+}
@@ -0,0 +1,46 @@
+use quad_tree::{quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_ts::QuadTreeTs};
+
+fn tree() -> (QuadTreeTs<usize>, Vec<QuadTreeBoundsTs>) {
+    // a small root, so the leaves end up in grown, wrapped and stuck nodes as well as deep ones
+    let tree = QuadTreeTs::new(0, 0, 64);
+    let boxes: Vec<QuadTreeBoundsTs> = (0..300i64)
+        .map(|i| QuadTreeBoundsTs::new((i * 37) % 500 - 100, (i * 91) % 500 - 100, 1 + i % 7, 1 + i % 4))
+        .collect();
+    for (i, b) in boxes.iter().enumerate() {
+        tree.insert(QuadTreeLeafTs::new(i, *b)).unwrap();
+    }
+    (tree, boxes)
+}
+
+#[test]
+fn nearest_leaves_come_back_closest_first() {
+    let (tree, boxes) = tree();
+    for (x, y) in [(50, 50), (-400, 20), (0, 390), (131, -77)] {
+        let mut results = Vec::new();
+        tree.nearest(x, y, 10, &mut results).unwrap();
+        assert_eq!(results.len(), 10);
+
+        let mut expected: Vec<f64> = boxes.iter().map(|b| b.distance(x, y)).collect();
+        expected.sort_by(|a, b| a.total_cmp(b));
+        let found: Vec<f64> = results.iter().map(|(_, d)| *d).collect();
+        assert_eq!(found, expected[..10]);
+        for (leaf, distance) in results.iter() {
+            assert_eq!(leaf.get_bounds().distance(x, y), *distance);
+        }
+    }
+}
+
+#[test]
+fn nearest_stops_at_the_tree_size() {
+    let (tree, _) = tree();
+    let mut results = Vec::new();
+    tree.nearest(0, 0, 1000, &mut results).unwrap();
+    assert_eq!(results.len(), 300);
+    assert!(results.windows(2).all(|w| w[0].1 <= w[1].1));
+
+    let mut results = Vec::new();
+    tree.nearest(0, 0, 0, &mut results).unwrap();
+    assert!(results.is_empty());
+    QuadTreeTs::<usize>::new(0, 0, 64).nearest(0, 0, 5, &mut results).unwrap();
+    assert!(results.is_empty());
+}
@@ -0,0 +1,153 @@
+use std::{cell::RefCell, rc::Rc};
+
+use quad_tree::{
+    quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_config::QuadTreeConfig, quad_tree_leaf_ts::QuadTreeLeafTs,
+    quad_tree_orientation::QuadTreeOrientation, quad_tree_ts::QuadTreeTs,
+};
+
+const COUNT: usize = 600;
+
+fn config(orientation: QuadTreeOrientation) -> QuadTreeConfig {
+    let mut config = QuadTreeConfig::new(2, 4, 16);
+    config.orientation = Some(orientation);
+    config
+}
+
+// the same boxes, scattered far enough outside the starting root to make both trees grow
+fn boxes() -> Vec<(i64, i64, i64, i64)> {
+    (0..COUNT as i64)
+        .map(|i| ((i * 37) % 700 - 250, (i * 53) % 600 - 200, 1 + i % 13, 1 + i % 7))
+        .collect()
+}
+
+struct Trees {
+    ts: QuadTreeTs<usize>,
+    single: Rc<RefCell<QuadTree<usize>>>,
+}
+
+fn build(orientation: QuadTreeOrientation) -> Trees {
+    let ts = QuadTreeTs::with_config(0, 0, 64, config(orientation));
+    let single = Rc::new(RefCell::new(QuadTree::with_config(true, 0, 0, 64, None, config(orientation))));
+    for (i, (x, y, w, h)) in boxes().into_iter().enumerate() {
+        ts.insert(QuadTreeLeafTs::new(i, QuadTreeBoundsTs::new(x, y, w, h))).unwrap();
+        QuadTree::insert(single.clone(), i as i64, i, QuadTreeBounds::new(x, y, w, h), 0).unwrap();
+    }
+    Trees { ts, single }
+}
+
+fn query_ts(trees: &Trees, x: i64, y: i64, w: i64, h: i64) -> Vec<usize> {
+    let mut results = Vec::new();
+    trees.ts.query(QuadTreeBoundsTs::new(x, y, w, h), &mut results).unwrap();
+    let mut items: Vec<usize> = results.iter().map(|leaf| leaf.get_item()).collect();
+    items.sort();
+    items
+}
+
+fn query_single(trees: &Trees, x: i64, y: i64, w: i64, h: i64) -> Vec<usize> {
+    let mut results = Vec::new();
+    QuadTree::query(trees.single.clone(), QuadTreeBounds::new(x, y, w, h), &mut results).unwrap();
+    let mut items: Vec<usize> = results.iter().map(|leaf| leaf.item).collect();
+    items.sort();
+    items
+}
+
+fn areas() -> Vec<(i64, i64, i64, i64)> {
+    (0..60).map(|i| ((i * 61) % 800 - 300, (i * 43) % 700 - 250, 20 + i % 90, 15 + i % 70)).collect()
+}
+
+#[test]
+fn both_trees_match_for_each_orientation() {
+    for orientation in [QuadTreeOrientation::YUp, QuadTreeOrientation::YDown] {
+        let trees = build(orientation);
+        assert_eq!(trees.ts.len(), COUNT);
+        assert_eq!(trees.single.borrow().len(), COUNT);
+
+        let ts_bounds = trees.ts.get_bounds();
+        let single_bounds = trees.single.borrow().bounds;
+        assert_eq!(
+            (ts_bounds.x, ts_bounds.y, ts_bounds.w, ts_bounds.h),
+            (single_bounds.x, single_bounds.y, single_bounds.w, single_bounds.h),
+            "{orientation:?}"
+        );
+        assert_eq!(trees.ts.branch_lens(), trees.single.borrow().branch_lens(), "{orientation:?}");
+
+        for (x, y, w, h) in areas() {
+            assert_eq!(query_ts(&trees, x, y, w, h), query_single(&trees, x, y, w, h), "{orientation:?}");
+        }
+    }
+}
+
+#[test]
+fn orientations_mirror_quadrants_and_agree_on_results() {
+    let up = build(QuadTreeOrientation::YUp);
+    let down = build(QuadTreeOrientation::YDown);
+
+    // the top quadrants of one are the bottom quadrants of the other
+    let [a, b, c, d] = up.ts.branch_lens();
+    assert_eq!(down.ts.branch_lens(), [d, c, b, a]);
+    let [a, b, c, d] = up.single.borrow().branch_lens();
+    assert_eq!(down.single.borrow().branch_lens(), [d, c, b, a]);
+
+    for (x, y, w, h) in areas() {
+        let expected: Vec<usize> = boxes()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (bx, by, bw, bh))| {
+                QuadTreeBoundsTs::new(x, y, w, h).intersects(QuadTreeBoundsTs::new(*bx, *by, *bw, *bh))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(query_ts(&up, x, y, w, h), expected);
+        assert_eq!(query_ts(&down, x, y, w, h), expected);
+        assert_eq!(query_single(&up, x, y, w, h), expected);
+        assert_eq!(query_single(&down, x, y, w, h), expected);
+    }
+}
+
+#[test]
+fn points_and_circles_match_across_trees() {
+    for orientation in [QuadTreeOrientation::YUp, QuadTreeOrientation::YDown] {
+        let trees = build(orientation);
+        for i in 0..80i64 {
+            let (x, y) = ((i * 29) % 700 - 250, (i * 31) % 600 - 200);
+
+            let mut ts = Vec::new();
+            trees.ts.query_point(x, y, &mut ts).unwrap();
+            let mut ts: Vec<usize> = ts.iter().map(|leaf| leaf.get_item()).collect();
+            ts.sort();
+            let mut single = Vec::new();
+            QuadTree::query_point(trees.single.clone(), x, y, &mut single).unwrap();
+            let mut single: Vec<usize> = single.iter().map(|leaf| leaf.item).collect();
+            single.sort();
+            assert_eq!(ts, single, "{orientation:?}");
+
+            let mut ts = Vec::new();
+            trees.ts.query_circle(x, y, 25, &mut ts).unwrap();
+            let mut ts: Vec<usize> = ts.iter().map(|leaf| leaf.get_item()).collect();
+            ts.sort();
+            let mut single = Vec::new();
+            QuadTree::query_circle(trees.single.clone(), x, y, 25, &mut single).unwrap();
+            let mut single: Vec<usize> = single.iter().map(|leaf| leaf.item).collect();
+            single.sort();
+            assert_eq!(ts, single, "{orientation:?}");
+        }
+    }
+}
+
+#[test]
+fn unset_orientation_keeps_each_trees_own_numbering() {
+    let config = QuadTreeConfig::new(2, 4, 16);
+    let ts = QuadTreeTs::with_config(0, 0, 64, config);
+    let single = Rc::new(RefCell::new(QuadTree::with_config(true, 0, 0, 64, None, config)));
+    for (i, (x, y, w, h)) in boxes().into_iter().enumerate() {
+        ts.insert(QuadTreeLeafTs::new(i, QuadTreeBoundsTs::new(x, y, w, h))).unwrap();
+        QuadTree::insert(single.clone(), i as i64, i, QuadTreeBounds::new(x, y, w, h), 0).unwrap();
+    }
+
+    assert_eq!(ts.branch_lens(), build(QuadTreeOrientation::YUp).ts.branch_lens());
+    assert_eq!(
+        single.borrow().branch_lens(),
+        build(QuadTreeOrientation::YDown).single.borrow().branch_lens()
+    );
+}
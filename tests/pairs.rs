@@ -0,0 +1,63 @@
+use std::ops::ControlFlow;
+
+use quad_tree::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_config::QuadTreeConfig, quad_tree_leaf_ts::QuadTreeLeafTs,
+    quad_tree_ts::QuadTreeTs,
+};
+
+const COUNT: usize = 700;
+
+fn boxes() -> Vec<QuadTreeBoundsTs> {
+    (0..COUNT as i64)
+        .map(|i| QuadTreeBoundsTs::new((i * 37) % 900 - 300, (i * 53) % 700 - 200, 1 + i % 47, 1 + i % 29))
+        .collect()
+}
+
+#[test]
+fn every_overlapping_pair_is_reported_once() {
+    let boxes = boxes();
+    let mut expected = Vec::new();
+    for i in 0..COUNT {
+        for j in i + 1..COUNT {
+            if boxes[i].intersects(boxes[j]) {
+                expected.push((i, j));
+            }
+        }
+    }
+    assert!(expected.len() > 50);
+
+    for looseness in [1.0, 1.5, 2.0, 3.0] {
+        let mut config = QuadTreeConfig::new(2, 4, 32);
+        config.looseness = looseness;
+        // the root starts small, so the boxes are spread over grown, wrapped and stuck nodes
+        let tree = QuadTreeTs::with_config(0, 0, 256, config);
+        for (i, b) in boxes.iter().enumerate() {
+            tree.insert(QuadTreeLeafTs::new(i, *b)).unwrap();
+        }
+
+        let mut results = Vec::new();
+        tree.pairs(&mut results);
+        let mut found: Vec<(usize, usize)> = results
+            .iter()
+            .map(|(a, b)| (a.get_item().min(b.get_item()), a.get_item().max(b.get_item())))
+            .collect();
+        found.sort();
+        assert_eq!(found, expected, "looseness {looseness}");
+    }
+}
+
+#[test]
+fn pairs_with_stops_when_asked() {
+    let tree = QuadTreeTs::new(0, 0, 256);
+    for (i, b) in boxes().into_iter().enumerate() {
+        tree.insert(QuadTreeLeafTs::new(i, b)).unwrap();
+    }
+
+    let mut visited = 0;
+    let flow = tree.pairs_with(|_, _| {
+        visited += 1;
+        if visited == 5 { ControlFlow::Break(visited) } else { ControlFlow::Continue(()) }
+    });
+    assert_eq!(flow, ControlFlow::Break(5));
+    assert_eq!(visited, 5);
+}
@@ -0,0 +1,58 @@
+use quad_tree::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_config::QuadTreeConfig, quad_tree_leaf_ts::QuadTreeLeafTs,
+    quad_tree_ts::QuadTreeTs,
+};
+
+fn items(results: &[QuadTreeLeafTs<usize>]) -> Vec<usize> {
+    let mut items: Vec<usize> = results.iter().map(|l| l.get_item()).collect();
+    items.sort();
+    items
+}
+
+fn check(tree: &QuadTreeTs<usize>) {
+    for q in 0..120i64 {
+        let area = QuadTreeBoundsTs::new((q * 97) % 2400 - 1200, (q * 61) % 2400 - 1200, 20 + q * 9, 15 + q * 7);
+        let (mut serial, mut parallel) = (Vec::new(), Vec::new());
+        tree.query(area, &mut serial).unwrap();
+        tree.query_parallel(area, &mut parallel).unwrap();
+        assert_eq!(items(&parallel), items(&serial));
+    }
+
+    let everything = QuadTreeBoundsTs::new(-5000, -5000, 10000, 10000);
+    let mut parallel = Vec::new();
+    tree.query_parallel(everything, &mut parallel).unwrap();
+    assert_eq!(items(&parallel), (0..tree.len()).collect::<Vec<_>>());
+}
+
+#[test]
+fn parallel_queries_match_serial_ones() {
+    for looseness in [1.0, 2.0] {
+        let mut config = QuadTreeConfig::new(4, 2, 32);
+        config.looseness = looseness;
+        let tree = QuadTreeTs::with_config(-1024, -1024, 2048, config);
+        for i in 0..3000i64 {
+            let bounds = QuadTreeBoundsTs::new((i * 7919) % 2000 - 1000, (i * 104729) % 2000 - 1000, 1 + i % 7, 1 + i % 4);
+            tree.insert(QuadTreeLeafTs::new(i as usize, bounds)).unwrap();
+        }
+        check(&tree);
+    }
+}
+
+#[test]
+fn parallel_queries_see_through_grown_roots() {
+    // a tiny root that has to grow, and wrap its branches, many times over
+    let tree = QuadTreeTs::with_config(0, 0, 8, QuadTreeConfig::new(2, 2, 32));
+    for i in 0..2000i64 {
+        let bounds = QuadTreeBoundsTs::new((i * 37) % 2000 - 1000, (i * 53) % 1800 - 900, 2 + i % 5, 3);
+        tree.insert(QuadTreeLeafTs::new(i as usize, bounds)).unwrap();
+    }
+    assert!(tree.get_bounds().w > 1000);
+    check(&tree);
+
+    // and a root that only holds leaves of its own, with nothing to fan out over
+    let small = QuadTreeTs::new(0, 0, 64);
+    small.insert(QuadTreeLeafTs::new(0, QuadTreeBoundsTs::new(1, 1, 2, 2))).unwrap();
+    let mut results = Vec::new();
+    small.query_parallel(QuadTreeBoundsTs::new(0, 0, 10, 10), &mut results).unwrap();
+    assert_eq!(items(&results), vec![0]);
+}
@@ -0,0 +1,105 @@
+use quad_tree::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_config::QuadTreeConfig, quad_tree_error::QuadTreeError,
+    quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_polygon_ts::QuadTreePolygonTs, quad_tree_ts::QuadTreeTs,
+};
+
+fn items(results: &[QuadTreeLeafTs<usize>]) -> Vec<usize> {
+    let mut items: Vec<usize> = results.iter().map(|l| l.get_item()).collect();
+    items.sort();
+    items.dedup();
+    items
+}
+
+fn tree(looseness: f64, boxes: &[QuadTreeBoundsTs]) -> QuadTreeTs<usize> {
+    let mut config = QuadTreeConfig::new(2, 2, 32);
+    config.looseness = looseness;
+    let tree = QuadTreeTs::with_config(0, 0, 64, config);
+    for (i, b) in boxes.iter().enumerate() {
+        tree.insert(QuadTreeLeafTs::new(i, *b)).unwrap();
+    }
+    tree
+}
+
+#[test]
+fn polygons_need_three_finite_points() {
+    assert_eq!(QuadTreePolygonTs::new(vec![(0, 0), (1, 1)]), Err(QuadTreeError::InvalidBounds));
+    assert_eq!(
+        QuadTreePolygonTs::new(vec![(0.0, 0.0), (1.0, f64::NAN), (1.0, 0.0)]),
+        Err(QuadTreeError::InvalidBounds)
+    );
+}
+
+#[test]
+fn convexity_follows_the_outline() {
+    let triangle = QuadTreePolygonTs::new(vec![(0, 0), (10, 0), (0, 10)]).unwrap();
+    assert!(triangle.is_convex());
+    assert!(triangle.overlaps(QuadTreeBoundsTs::new(1, 1, 2, 2)));
+    assert!(triangle.overlaps(QuadTreeBoundsTs::new(4, 4, 2, 2)));
+    assert!(!triangle.overlaps(QuadTreeBoundsTs::new(6, 6, 2, 2)));
+
+    // repeated points and straight runs leave a convex outline convex, in either winding
+    let square = QuadTreePolygonTs::new(vec![(0, 0), (0, 0), (5, 0), (10, 0), (10, 10), (0, 10), (0, 0)]).unwrap();
+    assert!(square.is_convex());
+    assert!(QuadTreePolygonTs::new(vec![(0, 10), (10, 10), (10, 0), (0, 0)]).unwrap().is_convex());
+
+    let l = QuadTreePolygonTs::new(vec![(0, 0), (100, 0), (100, 20), (20, 20), (20, 100), (0, 100)]).unwrap();
+    assert!(!l.is_convex());
+    assert!(l.contains_point(5, 50) && !l.contains_point(50, 50));
+    assert!(l.overlaps(QuadTreeBoundsTs::new(5, 50, 2, 2)));
+    assert!(!l.overlaps(QuadTreeBoundsTs::new(50, 50, 10, 10)));
+
+    // doubling back along an edge is no convex outline either
+    assert!(!QuadTreePolygonTs::new(vec![(0, 0), (10, 0), (5, 0), (5, 5)]).unwrap().is_convex());
+}
+
+#[test]
+fn a_pentagram_is_not_convex() {
+    // every turn goes the same way, but the outline goes round twice and leaves a hole in the middle
+    let star = QuadTreePolygonTs::new(vec![(0, 100), (59, -81), (-95, 31), (95, 31), (-59, -81)]).unwrap();
+    assert!(!star.is_convex());
+
+    let hole = QuadTreeBoundsTs::new(-5, -5, 10, 10);
+    assert!(!star.contains_point(0, 0));
+    assert!(!star.overlaps(hole));
+    assert!(star.overlaps(QuadTreeBoundsTs::new(-2, 80, 4, 4)));
+
+    let tree = tree(1.0, &[hole, QuadTreeBoundsTs::new(-2, 80, 4, 4)]);
+    let mut results = Vec::new();
+    tree.query_polygon(&star, &mut results);
+    assert_eq!(items(&results), vec![1]);
+}
+
+#[test]
+fn polygon_queries_match_brute_force() {
+    let boxes: Vec<QuadTreeBoundsTs> = (0..600i64)
+        .map(|i| QuadTreeBoundsTs::new((i * 37) % 300 - 100, (i * 53) % 300 - 100, 1 + i % 9, 1 + i % 6))
+        .collect();
+    let l = QuadTreePolygonTs::new(vec![(0, 0), (100, 0), (100, 20), (20, 20), (20, 100), (0, 100)]).unwrap();
+    for looseness in [1.0, 2.0] {
+        let tree = tree(looseness, &boxes);
+
+        // a rectangle outline matches the box query, and the L matches its two rectangles
+        let rectangle = QuadTreePolygonTs::new(vec![(-20, 10), (90, 10), (90, 70), (-20, 70)]).unwrap();
+        let (mut found, mut expected) = (Vec::new(), Vec::new());
+        tree.query_polygon(&rectangle, &mut found);
+        tree.query(QuadTreeBoundsTs::new(-20, 10, 110, 60), &mut expected).unwrap();
+        assert_eq!(items(&found), items(&expected));
+
+        let (mut found, mut expected) = (Vec::new(), Vec::new());
+        tree.query_polygon(&l, &mut found);
+        tree.query(QuadTreeBoundsTs::new(0, 0, 100, 20), &mut expected).unwrap();
+        tree.query(QuadTreeBoundsTs::new(0, 0, 20, 100), &mut expected).unwrap();
+        assert_eq!(items(&found), items(&expected));
+
+        let star = QuadTreePolygonTs::new(vec![(50, 150), (109, -31), (-45, 81), (145, 81), (-9, -31)]).unwrap();
+        let triangle = QuadTreePolygonTs::new(vec![(-50, -50), (150, -20), (0, 120)]).unwrap();
+        let kite = QuadTreePolygonTs::new(vec![(-90, 0), (0, -90), (150, 40), (30, 180)]).unwrap();
+        for polygon in [star, triangle, kite] {
+            let mut found = Vec::new();
+            tree.query_polygon(&polygon, &mut found);
+            let expected: Vec<usize> = (0..boxes.len()).filter(|&i| polygon.overlaps(boxes[i])).collect();
+            assert!(!expected.is_empty());
+            assert_eq!(items(&found), expected);
+        }
+    }
+}
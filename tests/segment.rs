@@ -0,0 +1,57 @@
+use quad_tree::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_config::QuadTreeConfig, quad_tree_error::QuadTreeError,
+    quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_ts::QuadTreeTs,
+};
+
+#[test]
+fn segments_enter_boxes_where_expected() {
+    let bounds = QuadTreeBoundsTs::new(10, 0, 10, 10);
+    assert_eq!(bounds.segment_entry(0, 5, 40, 5), Some(0.25));
+    assert_eq!(bounds.segment_entry(0, 50, 40, 50), None);
+    assert_eq!(bounds.segment_entry(0, 5, 5, 5), None);
+    // starting inside, or not moving at all inside, counts as entering straight away
+    assert_eq!(bounds.segment_entry(12, 5, 40, 5), Some(0.0));
+    assert_eq!(bounds.segment_entry(15, 5, 15, 5), Some(0.0));
+}
+
+#[test]
+fn segment_queries_match_brute_force() {
+    let boxes: Vec<QuadTreeBoundsTs> = (0..500i64)
+        .map(|i| QuadTreeBoundsTs::new((i * 37) % 900 - 300, (i * 53) % 700 - 200, 1 + i % 9, 1 + i % 6))
+        .collect();
+    for looseness in [1.0, 2.0] {
+        let mut config = QuadTreeConfig::new(2, 2, 32);
+        config.looseness = looseness;
+        let tree = QuadTreeTs::with_config(0, 0, 64, config);
+        for (i, b) in boxes.iter().enumerate() {
+            tree.insert(QuadTreeLeafTs::new(i, *b)).unwrap();
+        }
+
+        for k in 0..40i64 {
+            let (x0, y0) = ((k * 71) % 900 - 300, (k * 29) % 700 - 200);
+            let (x1, y1) = ((k * 113) % 900 - 300, (k * 47) % 700 - 200);
+            let mut results = Vec::new();
+            tree.query_segment(x0, y0, x1, y1, &mut results).unwrap();
+            assert!(results.windows(2).all(|w| w[0].1 <= w[1].1));
+
+            let mut items: Vec<usize> = results.iter().map(|(l, _)| l.get_item()).collect();
+            items.sort();
+            let expected: Vec<usize> =
+                (0..boxes.len()).filter(|&i| boxes[i].segment_entry(x0, y0, x1, y1).is_some()).collect();
+            assert_eq!(items, expected);
+        }
+    }
+}
+
+#[test]
+fn segment_hits_come_back_nearest_first() {
+    let tree: QuadTreeTs<usize, f64> = QuadTreeTs::auto();
+    tree.insert(QuadTreeLeafTs::new(1, QuadTreeBoundsTs::new(30.0, -1.0, 2.0, 2.0))).unwrap();
+    tree.insert(QuadTreeLeafTs::new(2, QuadTreeBoundsTs::new(10.0, -1.0, 2.0, 2.0))).unwrap();
+
+    let mut results = Vec::new();
+    tree.query_segment(0.0, 0.0, 100.0, 0.0, &mut results).unwrap();
+    let hits: Vec<(usize, f64)> = results.iter().map(|(l, d)| (l.get_item(), *d)).collect();
+    assert_eq!(hits, vec![(2, 10.0), (1, 30.0)]);
+    assert_eq!(tree.query_segment(f64::NAN, 0.0, 1.0, 0.0, &mut results), Err(QuadTreeError::InvalidBounds));
+}
@@ -0,0 +1,102 @@
+use std::{cell::RefCell, rc::Rc};
+
+use quad_tree::{
+    quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_branch_ts::QuadTreeBranchTs, quad_tree_config::QuadTreeConfig, quad_tree_leaf_ts::QuadTreeLeafTs,
+    quad_tree_ts::QuadTreeTs,
+};
+
+const FAR: i64 = 1_000_000_000;
+
+fn spot(i: i64) -> (i64, i64) {
+    ((i * 37) % 1000, (i * 53) % 1000)
+}
+
+#[test]
+fn roots_shrink_back_once_the_far_leaf_is_gone() {
+    for auto_shrink in [false, true] {
+        let config = QuadTreeConfig { auto_shrink, ..QuadTreeConfig::default() };
+        let tree = QuadTreeTs::with_config(0, 0, 1024, config);
+        for i in 0..200i64 {
+            let (x, y) = spot(i);
+            tree.insert(QuadTreeLeafTs::new(i, QuadTreeBoundsTs::new(x, y, 3, 3))).unwrap();
+        }
+
+        let stray = QuadTreeLeafTs::new(-1, QuadTreeBoundsTs::new(FAR, FAR, 3, 3));
+        tree.insert(stray.clone()).unwrap();
+        assert!(tree.get_bounds().w > FAR);
+        tree.remove(&stray).unwrap();
+        if !auto_shrink {
+            assert!(tree.shrink());
+        }
+        assert!(!tree.shrink());
+        assert_eq!(tree.get_bounds(), QuadTreeBoundsTs::new(0, 0, 1024, 1024));
+
+        let mut results = Vec::new();
+        tree.query(QuadTreeBoundsTs::new(-10, -10, 2000, 2000), &mut results).unwrap();
+        assert_eq!(results.len(), 200);
+        for leaf in results.iter() {
+            tree.remove(leaf).unwrap();
+        }
+        assert!(tree.is_empty());
+    }
+}
+
+#[test]
+fn single_threaded_roots_shrink_too() {
+    let root = Rc::new(RefCell::new(QuadTree::new(true, 0, 0, 1024, None)));
+    for i in 0..200i64 {
+        let (x, y) = spot(i);
+        QuadTree::insert(root.clone(), i, i, QuadTreeBounds::new(x, y, 3, 3), 0).unwrap();
+    }
+    QuadTree::insert(root.clone(), -1, -1, QuadTreeBounds::new(FAR, FAR, 3, 3), 0).unwrap();
+
+    let mut results = Vec::new();
+    QuadTree::query(root.clone(), QuadTreeBounds::new(FAR, FAR, 3, 3), &mut results).unwrap();
+    assert_eq!(results.len(), 1);
+    QuadTree::remove(&results[0]).unwrap();
+    assert!(QuadTree::shrink(root.clone()));
+    assert!(!QuadTree::shrink(root.clone()));
+    assert!(root.borrow().bounds.w <= 1024);
+    assert_eq!(root.borrow().len(), 200);
+}
+
+#[test]
+fn leaves_wrapped_by_a_grow_can_still_be_removed() {
+    let root = Rc::new(RefCell::new(QuadTree::new(true, 0, 0, 64, None)));
+    for i in 0..200i64 {
+        let (x, y) = spot(i);
+        QuadTree::insert(root.clone(), i, i, QuadTreeBounds::new(x / 16, y / 16, 2, 2), 0).unwrap();
+    }
+    // every grow wraps the branches that are already there
+    for i in 0..20i64 {
+        QuadTree::insert(root.clone(), 1000 + i, 1000 + i, QuadTreeBounds::new(-(64 << i), 64 << i, 2, 2), 0).unwrap();
+    }
+
+    let leaves: Vec<_> = QuadTree::leaves(root.clone()).collect();
+    assert_eq!(leaves.len(), 220);
+    for leaf in leaves.iter() {
+        QuadTree::remove(leaf).unwrap();
+    }
+    assert!(root.borrow().is_empty());
+}
+
+#[test]
+fn dropping_a_tree_leaves_branches_held_elsewhere_intact() {
+    let tree = QuadTreeTs::with_config(0, 0, 1 << 20, QuadTreeConfig::new(1, 1, 64));
+    for i in 0..64i64 {
+        tree.insert(QuadTreeLeafTs::new(i, QuadTreeBoundsTs::new(i * 3, i * 5, 1, 1))).unwrap();
+    }
+    // a branch a few levels down, with leaves of its own and below it
+    let branch = tree
+        .iter()
+        .filter_map(|leaf| leaf.get_parent())
+        .filter(|branch| branch.read().get_bounds() != tree.get_bounds())
+        .max_by_key(|branch| QuadTreeBranchTs::leaves(branch).count())
+        .unwrap();
+    let held = QuadTreeBranchTs::leaves(&branch).count();
+    assert!(held > 0);
+
+    drop(tree);
+    assert_eq!(QuadTreeBranchTs::leaves(&branch).count(), held);
+}
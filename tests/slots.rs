@@ -0,0 +1,115 @@
+use std::{cell::RefCell, rc::Rc, thread};
+
+use quad_tree::{
+    quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_config::QuadTreeConfig, quad_tree_error::QuadTreeError, quad_tree_leaf_ts::QuadTreeLeafTs,
+    quad_tree_ts::QuadTreeTs,
+};
+
+#[test]
+fn removals_from_one_crowded_list_keep_the_other_slots_right() {
+    let tree = QuadTreeTs::new(0, 0, 1024);
+    let mut leaves = Vec::new();
+    for i in 0..500usize {
+        // everything straddles the centre, so it all lands in the root's stuck list
+        let leaf = QuadTreeLeafTs::new(i, QuadTreeBoundsTs::new(500 - (i as i64 % 7), 500, 30, 30));
+        tree.insert(leaf.clone()).unwrap();
+        leaves.push(leaf);
+    }
+
+    for leaf in leaves.iter().step_by(3) {
+        tree.remove(leaf).unwrap();
+        assert_eq!(tree.remove(leaf), Err(QuadTreeError::Detached));
+    }
+    // moving out of the stuck list shifts the slots of whatever is swapped in
+    for leaf in leaves.iter_mut().skip(1).step_by(3) {
+        tree.update_bounds(leaf, QuadTreeBoundsTs::new(10, 10, 2, 2)).unwrap();
+    }
+
+    let mut items: Vec<usize> = tree.iter().map(|l| l.get_item()).collect();
+    items.sort();
+    assert_eq!(items, (0..500).filter(|i| i % 3 != 0).collect::<Vec<_>>());
+    for leaf in leaves.iter().filter(|l| l.get_item() % 3 != 0) {
+        tree.remove(leaf).unwrap();
+    }
+    assert!(tree.is_empty());
+}
+
+#[test]
+fn single_threaded_copies_follow_their_slot() {
+    let root = Rc::new(RefCell::new(QuadTree::<usize>::new(true, 0, 0, 1024, None)));
+    for i in 0..300usize {
+        QuadTree::insert(root.clone(), i as i64, i, QuadTreeBounds::new(500, 500 - (i as i64 % 5), 30, 30), 0)
+            .unwrap();
+    }
+
+    let mut results = Vec::new();
+    QuadTree::query(root.clone(), QuadTreeBounds::new(0, 0, 1024, 1024), &mut results).unwrap();
+    // every removal moves the last leaf into the hole, the copies share their slot with the leaf so they keep up
+    for leaf in results.iter() {
+        QuadTree::remove(leaf).unwrap();
+        assert_eq!(QuadTree::remove(leaf).err(), Some(QuadTreeError::Detached));
+    }
+    assert!(root.borrow().is_empty());
+}
+
+#[test]
+fn single_threaded_copies_follow_their_leaf_down_a_split() {
+    let config = QuadTreeConfig::new(4, 8, 16);
+    let root = Rc::new(RefCell::new(QuadTree::<usize>::with_config(true, 0, 0, 1024, None, config)));
+    for i in 0..4usize {
+        QuadTree::insert(root.clone(), i as i64, i, QuadTreeBounds::new(10 + i as i64 * 3, 10, 2, 2), 0).unwrap();
+    }
+    let mut copies = Vec::new();
+    QuadTree::query(root.clone(), QuadTreeBounds::new(0, 0, 1024, 1024), &mut copies).unwrap();
+    assert!(copies.iter().all(|leaf| Rc::ptr_eq(&leaf.get_parent().unwrap(), &root)));
+
+    // the root splits and pushes the first four down, the copies taken before still find them
+    for i in 4..200usize {
+        let (x, y) = ((i as i64 * 37) % 1000, (i as i64 * 53) % 1000);
+        QuadTree::insert(root.clone(), i as i64, i, QuadTreeBounds::new(x, y, 2, 2), 0).unwrap();
+    }
+    assert!(copies.iter().all(|leaf| !Rc::ptr_eq(&leaf.get_parent().unwrap(), &root)));
+    for leaf in copies.iter() {
+        QuadTree::remove(leaf).unwrap();
+    }
+    assert_eq!(root.borrow().len(), 196);
+}
+
+#[test]
+fn threads_moving_and_removing_their_own_leaves_never_lose_one() {
+    const THREADS: usize = 6;
+    const PER_THREAD: i64 = 200;
+
+    let tree = QuadTreeTs::with_config(0, 0, 512, QuadTreeConfig::new(2, 4, 16));
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let tree = tree.clone();
+            scope.spawn(move || {
+                let offset = t as i64 * 17;
+                for round in 0..5i64 {
+                    let mut leaves = Vec::new();
+                    for i in 0..PER_THREAD {
+                        let bounds = QuadTreeBoundsTs::new((i * 37 + offset) % 500, (i * 53 + round) % 500, 3, 3);
+                        let leaf = QuadTreeLeafTs::new(t, bounds);
+                        tree.insert(leaf.clone()).unwrap();
+                        leaves.push(leaf);
+                    }
+                    // other threads split, prune and re-parent the same nodes while these move
+                    for (i, leaf) in leaves.iter_mut().enumerate() {
+                        let b = leaf.get_bounds();
+                        let step = i as i64 % 9 - 4;
+                        tree.update_bounds(leaf, QuadTreeBoundsTs::new(b.x + step * 11, b.y - step * 7, 3, 3))
+                            .unwrap();
+                    }
+                    for leaf in leaves.iter() {
+                        tree.remove(leaf).unwrap();
+                    }
+                }
+            });
+        }
+    });
+
+    assert!(tree.is_empty());
+    assert_eq!(tree.iter().count(), 0);
+}
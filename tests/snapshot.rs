@@ -0,0 +1,118 @@
+use quad_tree::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_config::QuadTreeConfig, quad_tree_error::QuadTreeError,
+    quad_tree_leaf_ts::QuadTreeLeafTs,
+    quad_tree_snapshot_ts::{QuadTreeLeafSnapshotTs, QuadTreeNodeSnapshotTs, QuadTreeSnapshotTs},
+    quad_tree_ts::QuadTreeTs,
+};
+
+fn tree() -> QuadTreeTs<usize> {
+    let tree = QuadTreeTs::with_config(0, 0, 256, QuadTreeConfig::new(2, 4, 16));
+    for i in 0..200i64 {
+        let bounds = QuadTreeBoundsTs::new((i * 37) % 400 - 80, (i * 53) % 300 - 20, 1 + i % 11, 1 + i % 5);
+        tree.insert(QuadTreeLeafTs::new(i as usize, bounds)).unwrap();
+    }
+    tree
+}
+
+fn sorted(tree: &QuadTreeTs<usize>) -> Vec<(i64, usize, QuadTreeBoundsTs)> {
+    let mut all: Vec<_> = tree.iter().map(|l| (l.get_identity(), l.get_item(), l.get_bounds())).collect();
+    all.sort_by_key(|(identity, _, _)| *identity);
+    all
+}
+
+#[test]
+fn restored_trees_match_and_stay_usable() {
+    let tree = tree();
+    let restored = QuadTreeTs::restore(tree.snapshot()).unwrap();
+
+    assert_eq!(sorted(&restored), sorted(&tree));
+    assert_eq!(restored.branch_lens(), tree.branch_lens());
+    assert_eq!(restored.nodes().count(), tree.nodes().count());
+
+    // restored leaves know their parents, so they can be moved and removed
+    let mut leaves: Vec<_> = restored.iter().collect();
+    for (i, leaf) in leaves.iter_mut().enumerate() {
+        assert!(leaf.get_parent().is_some());
+        let b = leaf.get_bounds();
+        restored.update_bounds(leaf, QuadTreeBoundsTs::new(b.x + i as i64 % 9, b.y - 3, b.w, b.h)).unwrap();
+    }
+    for leaf in leaves.iter().step_by(2) {
+        restored.remove(leaf).unwrap();
+    }
+    assert_eq!(restored.len(), 100);
+    assert_eq!(tree.len(), 200);
+}
+
+#[test]
+fn serialized_trees_load_back() {
+    let tree = tree();
+    let json = serde_json::to_string(&tree).unwrap();
+    let loaded: QuadTreeTs<usize> = serde_json::from_str(&json).unwrap();
+
+    assert_eq!(sorted(&loaded), sorted(&tree));
+    assert_eq!(loaded.branch_lens(), tree.branch_lens());
+    let area = QuadTreeBoundsTs::new(20, 10, 150, 120);
+    let (mut found, mut expected) = (Vec::new(), Vec::new());
+    loaded.query(area, &mut found).unwrap();
+    tree.query(area, &mut expected).unwrap();
+    let mut found: Vec<usize> = found.iter().map(|l| l.get_item()).collect();
+    let mut expected: Vec<usize> = expected.iter().map(|l| l.get_item()).collect();
+    found.sort();
+    expected.sort();
+    assert_eq!(found, expected);
+
+    // the loaded leaves are hung in their nodes, not just listed
+    let leaves: Vec<_> = loaded.iter().collect();
+    for leaf in leaves.iter() {
+        assert!(leaf.get_parent().is_some());
+        loaded.remove(leaf).unwrap();
+    }
+    assert!(loaded.is_empty());
+
+    let broken = json.replacen("\"w\":256", "\"w\":-256", 1);
+    assert_ne!(broken, json);
+    assert!(serde_json::from_str::<QuadTreeTs<usize>>(&broken).is_err());
+}
+
+#[test]
+fn the_last_identity_restores() {
+    let mut snapshot = QuadTreeTs::<usize>::new(0, 0, 64).snapshot();
+    snapshot.root.items.push(QuadTreeLeafSnapshotTs {
+        identity: i64::MAX,
+        bounds: QuadTreeBoundsTs::new(1, 1, 1, 1),
+        item: 7,
+    });
+
+    let restored = QuadTreeTs::restore(snapshot).unwrap();
+    assert_eq!(restored.iter().next().unwrap().get_identity(), i64::MAX);
+}
+
+fn first_branch(snapshot: &mut QuadTreeSnapshotTs<usize>) -> &mut QuadTreeNodeSnapshotTs<usize> {
+    snapshot.root.branches.iter_mut().flatten().next().unwrap()
+}
+
+#[test]
+fn snapshots_that_do_not_nest_are_refused() {
+    let tree = tree();
+
+    let mut snapshot = tree.snapshot();
+    first_branch(&mut snapshot).bounds.x += 10_000;
+    assert_eq!(QuadTreeTs::restore(snapshot).err(), Some(QuadTreeError::InvalidBounds));
+
+    let mut snapshot = tree.snapshot();
+    let mut branch = first_branch(&mut snapshot);
+    while branch.items.is_empty() && branch.stuck.is_empty() {
+        branch = branch.branches.iter_mut().flatten().next().unwrap();
+    }
+    let leaf = branch.items.iter_mut().chain(branch.stuck.iter_mut()).next().unwrap();
+    leaf.bounds.y -= 10_000;
+    assert_eq!(QuadTreeTs::restore(snapshot).err(), Some(QuadTreeError::InvalidBounds));
+
+    let mut snapshot = tree.snapshot();
+    snapshot.root.stuck.push(QuadTreeLeafSnapshotTs {
+        identity: 0,
+        bounds: QuadTreeBoundsTs::new(1, 1, 0, 1),
+        item: 0,
+    });
+    assert_eq!(QuadTreeTs::restore(snapshot).err(), Some(QuadTreeError::InvalidBounds));
+}
@@ -0,0 +1,69 @@
+use std::{sync::mpsc, thread};
+
+use quad_tree::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_error::QuadTreeError, quad_tree_leaf_ts::QuadTreeLeafTs,
+    quad_tree_ts::QuadTreeTs,
+};
+
+fn tree() -> QuadTreeTs<usize> {
+    let tree = QuadTreeTs::new(0, 0, 512);
+    for i in 0..3000i64 {
+        let bounds = QuadTreeBoundsTs::new((i * 7919) % 1000, (i * 104729) % 1000, 5, 5);
+        tree.insert(QuadTreeLeafTs::new(i as usize, bounds)).unwrap();
+    }
+    tree
+}
+
+fn expected(tree: &QuadTreeTs<usize>, area: QuadTreeBoundsTs) -> Vec<usize> {
+    let mut results = Vec::new();
+    tree.query(area, &mut results).unwrap();
+    let mut items: Vec<usize> = results.iter().map(|l| l.get_item()).collect();
+    items.sort();
+    items
+}
+
+#[test]
+fn streamed_leaves_match_a_collected_query() {
+    let tree = tree();
+    let area = QuadTreeBoundsTs::new(100, 100, 600, 500);
+
+    let (sender, receiver) = mpsc::channel();
+    let consumer = thread::spawn(move || {
+        let mut items: Vec<usize> = receiver.iter().map(|l: QuadTreeLeafTs<usize>| l.get_item()).collect();
+        items.sort();
+        items
+    });
+    tree.query_send(area, &sender).unwrap();
+    drop(sender);
+    assert_eq!(consumer.join().unwrap(), expected(&tree, area));
+}
+
+#[test]
+fn batches_never_exceed_the_batch_size() {
+    let tree = tree();
+    let area = QuadTreeBoundsTs::new(100, 100, 600, 500);
+
+    let (sender, receiver) = mpsc::channel();
+    tree.query_send_batched(area, 64, &sender).unwrap();
+    drop(sender);
+    let batches: Vec<Vec<QuadTreeLeafTs<usize>>> = receiver.iter().collect();
+    assert!(batches.len() > 1);
+    assert!(batches.iter().all(|b| !b.is_empty() && b.len() <= 64));
+    let mut items: Vec<usize> = batches.iter().flatten().map(|l| l.get_item()).collect();
+    items.sort();
+    assert_eq!(items, expected(&tree, area));
+}
+
+#[test]
+fn a_dropped_receiver_stops_the_walk() {
+    let tree = tree();
+    let area = QuadTreeBoundsTs::new(0, 0, 1000, 1000);
+
+    let (sender, receiver) = mpsc::channel();
+    drop(receiver);
+    assert_eq!(tree.query_send(area, &sender), Err(QuadTreeError::Disconnected));
+
+    let (sender, receiver) = mpsc::channel::<Vec<QuadTreeLeafTs<usize>>>();
+    drop(receiver);
+    assert_eq!(tree.query_send_batched(area, 10, &sender), Err(QuadTreeError::Disconnected));
+}
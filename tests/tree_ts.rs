@@ -0,0 +1,38 @@
+use quad_tree::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_error::QuadTreeError, quad_tree_leaf_ts::QuadTreeLeafTs,
+    quad_tree_ts::QuadTreeTs,
+};
+
+#[test]
+fn clones_share_one_tree() {
+    let tree = QuadTreeTs::new(0, 0, 64);
+    let other = tree.clone();
+    let leaf = QuadTreeLeafTs::new(1usize, QuadTreeBoundsTs::new(3, 3, 2, 2));
+    tree.insert(leaf.clone()).unwrap();
+
+    assert_eq!(other.len(), 1);
+    other.remove(&leaf).unwrap();
+    assert!(tree.is_empty());
+}
+
+#[test]
+fn leaves_of_another_tree_are_not_found() {
+    let tree = QuadTreeTs::new(0, 0, 64);
+    let other = QuadTreeTs::new(0, 0, 64);
+    let mut leaves = Vec::new();
+    for i in 0..50i64 {
+        let leaf = QuadTreeLeafTs::new(i as usize, QuadTreeBoundsTs::new(i, 63 - i, 1, 1));
+        other.insert(leaf.clone()).unwrap();
+        leaves.push(leaf);
+    }
+
+    for leaf in leaves.iter_mut() {
+        assert_eq!(tree.remove(leaf), Err(QuadTreeError::NotFound));
+        let moved = QuadTreeBoundsTs::new(10, 10, 1, 1);
+        assert_eq!(tree.update_bounds(leaf, moved), Err(QuadTreeError::NotFound));
+    }
+    assert_eq!(other.len(), 50);
+
+    let stray = QuadTreeLeafTs::new(0, QuadTreeBoundsTs::new(1, 1, 1, 1));
+    assert_eq!(tree.remove(&stray), Err(QuadTreeError::Detached));
+}
@@ -0,0 +1,134 @@
+use std::thread;
+
+use quad_tree::{
+    quad_tree_bounds_ts::QuadTreeBoundsTs, quad_tree_config::QuadTreeConfig, quad_tree_error::QuadTreeError,
+    quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_ts::QuadTreeTs,
+};
+
+fn found(tree: &QuadTreeTs<usize>, leaf: &QuadTreeLeafTs<usize>) -> usize {
+    let mut results = Vec::new();
+    tree.query(leaf.get_bounds(), &mut results).unwrap();
+    results.iter().filter(|r| r.get_identity() == leaf.get_identity()).count()
+}
+
+#[test]
+fn moved_leaves_are_found_at_their_new_bounds() {
+    let tree = QuadTreeTs::new(0, 0, 256);
+    let mut leaves = Vec::new();
+    for i in 0..200i64 {
+        let leaf = QuadTreeLeafTs::new(i as usize, QuadTreeBoundsTs::new((i * 13) % 250, (i * 29) % 250, 1 + i % 9, 2));
+        tree.insert(leaf.clone()).unwrap();
+        leaves.push(leaf);
+    }
+
+    // small steps, then one far enough out to make the root grow
+    for step in 0..10i64 {
+        for (i, leaf) in leaves.iter_mut().enumerate() {
+            let b = leaf.get_bounds();
+            let i = i as i64;
+            tree.update_bounds(leaf, QuadTreeBoundsTs::new(b.x + i % 7 - 3 + step, b.y - i % 5 + 2, b.w, b.h))
+                .unwrap();
+        }
+    }
+    tree.update_bounds(&mut leaves[0], QuadTreeBoundsTs::new(5000, -5000, 3, 3)).unwrap();
+
+    assert_eq!(tree.len(), 200);
+    for leaf in leaves.iter() {
+        assert!(leaf.get_parent().is_some());
+        assert_eq!(found(&tree, leaf), 1);
+    }
+}
+
+#[test]
+fn a_move_the_root_cannot_reach_keeps_the_leaf() {
+    let tree = QuadTreeTs::new(0, 0, 64);
+    let mut leaf = QuadTreeLeafTs::new(1, QuadTreeBoundsTs::new(1, 1, 1, 1));
+    tree.insert(leaf.clone()).unwrap();
+
+    let far = QuadTreeBoundsTs::new(i64::MAX - 10, 0, 5, 5);
+    assert_eq!(tree.update_bounds(&mut leaf, far), Err(QuadTreeError::OutOfRange));
+
+    assert_eq!(tree.len(), 1);
+    assert!(leaf.get_parent().is_some());
+    assert_eq!(leaf.get_bounds(), QuadTreeBoundsTs::new(1, 1, 1, 1));
+    assert_eq!(found(&tree, &leaf), 1);
+    tree.remove(&leaf).unwrap();
+}
+
+#[test]
+fn bad_bounds_and_detached_leaves_are_refused() {
+    let tree = QuadTreeTs::new(0, 0, 64);
+    let mut leaf = QuadTreeLeafTs::new(1, QuadTreeBoundsTs::new(1, 1, 1, 1));
+    assert_eq!(
+        tree.update_bounds(&mut leaf, QuadTreeBoundsTs::new(2, 2, 1, 1)),
+        Err(QuadTreeError::Detached)
+    );
+
+    tree.insert(leaf.clone()).unwrap();
+    assert_eq!(
+        tree.update_bounds(&mut leaf, QuadTreeBoundsTs::new(2, 2, 0, 1)),
+        Err(QuadTreeError::InvalidBounds)
+    );
+    assert_eq!(
+        tree.update_bounds(&mut leaf, QuadTreeBoundsTs::new(2, i64::MAX, 1, 1)),
+        Err(QuadTreeError::OutOfRange)
+    );
+    assert_eq!(found(&tree, &leaf), 1);
+}
+
+#[test]
+fn threads_moving_leaves_across_nodes_never_lose_one() {
+    const THREADS: usize = 8;
+    const PER_THREAD: usize = 100;
+
+    for auto_shrink in [false, true] {
+        // one leaf per node and no floor on the size, so nearly every move leaves a node to be pruned
+        let config = QuadTreeConfig { auto_shrink, ..QuadTreeConfig::new(1, 1, 32) };
+        let tree = QuadTreeTs::with_config(0, 0, 512, config);
+        let leaves: Vec<Vec<QuadTreeLeafTs<usize>>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..THREADS)
+                .map(|t| {
+                    let tree = tree.clone();
+                    scope.spawn(move || {
+                        let mut seed = t as i64 * 7919 + 1;
+                        let mut spot = move || {
+                            seed = (seed * 1103515245 + 12345) % 2147483648;
+                            (seed % 300 - 20, (seed / 300) % 300 - 20)
+                        };
+                        let mut leaves = Vec::new();
+                        for i in 0..PER_THREAD {
+                            let (x, y) = spot();
+                            let leaf = QuadTreeLeafTs::new(t * PER_THREAD + i, QuadTreeBoundsTs::new(x, y, 2, 2));
+                            tree.insert(leaf.clone()).unwrap();
+                            leaves.push(leaf);
+                        }
+                        for round in 0..100 {
+                            for leaf in leaves.iter_mut() {
+                                let (x, y) = spot();
+                                tree.update_bounds(leaf, QuadTreeBoundsTs::new(x, y, 2, 2)).unwrap();
+                            }
+                            // some leaves go out and come back in while the others keep moving
+                            for leaf in leaves.iter().skip(round % 3).step_by(3) {
+                                tree.remove(leaf).unwrap();
+                                tree.insert(leaf.clone()).unwrap();
+                            }
+                        }
+                        leaves
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+        });
+
+        assert_eq!(tree.len(), THREADS * PER_THREAD);
+        assert_eq!(tree.iter().count(), THREADS * PER_THREAD);
+        for leaf in leaves.iter().flatten() {
+            assert!(leaf.get_parent().is_some());
+            assert_eq!(found(&tree, leaf), 1);
+        }
+        for leaf in leaves.iter().flatten() {
+            tree.remove(leaf).unwrap();
+        }
+        assert!(tree.is_empty());
+    }
+}
@@ -0,0 +1,106 @@
+use std::{cell::RefCell, ops::ControlFlow, rc::Rc, sync::mpsc};
+
+use quad_tree::{
+    quad_tree::QuadTree, quad_tree_bounds::QuadTreeBounds, quad_tree_bounds_ts::QuadTreeBoundsTs,
+    quad_tree_error::QuadTreeError, quad_tree_leaf_ts::QuadTreeLeafTs, quad_tree_ts::QuadTreeTs,
+};
+
+#[test]
+fn corners_come_in_any_order() {
+    assert_eq!(QuadTreeBoundsTs::from_corners(5, 9, 1, 2), Ok(QuadTreeBoundsTs::new(1, 2, 4, 7)));
+    let b = QuadTreeBounds::from_corners(5.0, 2.0, 1.0, 9.0).unwrap();
+    assert_eq!((b.x, b.y, b.w, b.h), (1.0, 2.0, 4.0, 7.0));
+}
+
+#[test]
+fn corners_too_far_apart_are_out_of_range() {
+    assert_eq!(QuadTreeBoundsTs::from_corners(i64::MIN, 0, i64::MAX, 1), Err(QuadTreeError::OutOfRange));
+    assert_eq!(QuadTreeBoundsTs::from_corners(0, i64::MAX, 1, -2), Err(QuadTreeError::OutOfRange));
+    assert!(QuadTreeBounds::from_corners(i64::MIN, 0, i64::MAX, 1).is_err());
+    assert!(QuadTreeBoundsTs::from_corners(f64::MAX, 0.0, -f64::MAX, 1.0).is_err());
+}
+
+#[test]
+fn bad_bounds_are_refused_everywhere() {
+    let tree: QuadTreeTs<u8> = QuadTreeTs::new(0, 0, 64);
+    tree.insert(QuadTreeLeafTs::new(0, QuadTreeBoundsTs::new(1, 1, 2, 2))).unwrap();
+    let flat = QuadTreeBoundsTs::from_corners(3, 3, 3, 9).unwrap();
+    let visit = |_: &QuadTreeLeafTs<u8>| ControlFlow::<()>::Continue(());
+
+    for bad in [QuadTreeBoundsTs::new(0, 0, -5, 0), QuadTreeBoundsTs::new(0, 0, 0, 3), flat] {
+        assert_eq!(tree.insert(QuadTreeLeafTs::new(1, bad)), Err(QuadTreeError::InvalidBounds));
+        assert_eq!(tree.query(bad, &mut Vec::new()), Err(QuadTreeError::InvalidBounds));
+        assert_eq!(tree.query_parallel(bad, &mut Vec::new()), Err(QuadTreeError::InvalidBounds));
+        assert_eq!(tree.query_with(bad, visit), Err(QuadTreeError::InvalidBounds));
+
+        let (sender, _receiver) = mpsc::channel();
+        assert_eq!(tree.query_send(bad, &sender), Err(QuadTreeError::InvalidBounds));
+        let (sender, _receiver) = mpsc::channel();
+        assert_eq!(tree.query_send_batched(bad, 4, &sender), Err(QuadTreeError::InvalidBounds));
+    }
+    assert_eq!(tree.query_circle(0, 0, -1, &mut Vec::new()), Err(QuadTreeError::InvalidBounds));
+    assert_eq!(tree.query_circle_with(0, 0, -1, visit), Err(QuadTreeError::InvalidBounds));
+    assert_eq!(tree.len(), 1);
+}
+
+#[test]
+fn nan_points_and_radii_are_refused() {
+    let tree: QuadTreeTs<u8, f64> = QuadTreeTs::new(0.0, 0.0, 64.0);
+    let visit = |_: &QuadTreeLeafTs<u8, f64>| ControlFlow::<()>::Continue(());
+    assert_eq!(
+        tree.insert(QuadTreeLeafTs::new(1, QuadTreeBoundsTs::new(f64::NAN, 0.0, 1.0, 1.0))),
+        Err(QuadTreeError::InvalidBounds)
+    );
+    assert_eq!(
+        tree.insert(QuadTreeLeafTs::new(1, QuadTreeBoundsTs::new(0.0, 0.0, f64::NAN, 1.0))),
+        Err(QuadTreeError::InvalidBounds)
+    );
+    assert_eq!(tree.query_point(f64::NAN, 1.0, &mut Vec::new()), Err(QuadTreeError::InvalidBounds));
+    assert_eq!(tree.query_point_with(1.0, f64::INFINITY, visit), Err(QuadTreeError::InvalidBounds));
+    assert_eq!(tree.query_circle(0.0, 0.0, f64::NAN, &mut Vec::new()), Err(QuadTreeError::InvalidBounds));
+    assert_eq!(tree.query_circle_with(f64::NAN, 0.0, 1.0, visit), Err(QuadTreeError::InvalidBounds));
+    assert_eq!(tree.nearest(f64::NAN, 0.0, 3, &mut Vec::new()), Err(QuadTreeError::InvalidBounds));
+    assert_eq!(tree.nearest(0.0, f64::NEG_INFINITY, 3, &mut Vec::new()), Err(QuadTreeError::InvalidBounds));
+
+    let single = Rc::new(RefCell::new(QuadTree::<u8, f64>::new(true, 0.0, 0.0, 64.0, None)));
+    assert_eq!(
+        QuadTree::query_point(single.clone(), f64::NAN, 0.0, &mut Vec::new()),
+        Err(QuadTreeError::InvalidBounds)
+    );
+    assert_eq!(
+        QuadTree::insert(single, 1, 1, QuadTreeBounds::new(0.0, 0.0, -5.0, 1.0), 0),
+        Err(QuadTreeError::InvalidBounds)
+    );
+}
+
+#[test]
+fn visitors_stop_when_asked() {
+    let tree: QuadTreeTs<usize> = QuadTreeTs::new(0, 0, 64);
+    for i in 0..400i64 {
+        let bounds = QuadTreeBoundsTs::new((i * 37) % 900 - 300, (i * 53) % 900 - 300, 5, 5);
+        tree.insert(QuadTreeLeafTs::new(i as usize, bounds)).unwrap();
+    }
+    let area = QuadTreeBoundsTs::new(-100, -50, 400, 300);
+    let mut results = Vec::new();
+    tree.query(area, &mut results).unwrap();
+
+    let mut visited = 0;
+    let flow = tree
+        .query_with(area, |_| {
+            visited += 1;
+            ControlFlow::<()>::Continue(())
+        })
+        .unwrap();
+    assert!(flow.is_continue());
+    assert_eq!(visited, results.len());
+
+    let mut visited = 0;
+    let flow = tree
+        .query_with(area, |leaf| {
+            visited += 1;
+            if visited == 3 { ControlFlow::Break(leaf.get_item()) } else { ControlFlow::Continue(()) }
+        })
+        .unwrap();
+    assert!(flow.is_break());
+    assert_eq!(visited, 3);
+}